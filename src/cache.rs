@@ -0,0 +1,68 @@
+use crate::utils::digest::digest;
+use std::{
+    ffi::{OsStr, OsString},
+    fs, io, iter,
+    path::{Path, PathBuf},
+};
+
+// Content-addressed store of built shared objects. Entries are published with
+// an atomic rename, so concurrent runs sharing the directory never observe a
+// partially written library.
+pub(crate) struct Cache {
+    directory: PathBuf,
+}
+
+impl Cache {
+    pub(crate) fn new<P: AsRef<Path>>(directory: P) -> io::Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+        Ok(Cache { directory })
+    }
+
+    // The key covers the compiler and every argument passed to it, including
+    // the contents of the files they name.
+    pub(crate) fn key<S: AsRef<OsStr>>(
+        compiler: &str,
+        arguments: impl Iterator<Item = S>,
+    ) -> String {
+        digest(
+            iter::once(OsString::from(compiler))
+                .chain(arguments.map(|argument| argument.as_ref().to_os_string())),
+        )
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<PathBuf> {
+        let path = self.path(key);
+        if path.exists() { Some(path) } else { None }
+    }
+
+    pub(crate) fn temporary(&self, key: &str) -> PathBuf {
+        self.directory.join(format!(
+            "{}.{}.{:016x}.tmp",
+            key,
+            std::process::id(),
+            rand::random::<u64>()
+        ))
+    }
+
+    pub(crate) fn insert(&self, key: &str, built: &Path) -> io::Result<PathBuf> {
+        let path = self.path(key);
+        fs::rename(built, &path)?;
+        Ok(path)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.so", key))
+    }
+}
+
+// Places a cached library at `destination`, preferring a hard link over a copy.
+pub(crate) fn install(cached: &Path, destination: &Path) -> io::Result<()> {
+    if destination.exists() {
+        fs::remove_file(destination)?;
+    }
+    if fs::hard_link(cached, destination).is_err() {
+        fs::copy(cached, destination)?;
+    }
+    Ok(())
+}
@@ -1,4 +1,10 @@
-use std::{error, ffi::OsStr, fmt, io::Read, path::Path, process::Command};
+use std::{
+    error,
+    ffi::OsStr,
+    fmt,
+    path::Path,
+    process::{Command, Stdio},
+};
 
 #[derive(Debug)]
 pub(crate) enum Error {
@@ -30,26 +36,20 @@ pub(crate) fn compile<S: AsRef<OsStr>>(
         .arg("-shared")
         .arg("-o")
         .arg(output)
-        .args(arguments);
+        .args(arguments)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::piped());
 
-    let mut compiler = compiler.spawn().map_err(|_| Error::Spawn)?;
-    let status = compiler.wait().map_err(|_| Error::Spawn)?;
+    let output = compiler.output().map_err(|_| Error::Spawn)?;
 
-    if status.success() {
+    if output.status.success() {
         Ok(())
     } else {
-        let reason = compiler
-            .stderr
-            .take()
-            .map(|mut stderr| {
-                let mut buffer = String::new();
-                if stderr.read_to_string(&mut buffer).is_ok() {
-                    Some(buffer)
-                } else {
-                    None
-                }
-            })
-            .flatten();
-        Err(Error::Compilation(reason))
+        let reason = String::from_utf8_lossy(&output.stderr).into_owned();
+        Err(Error::Compilation(if reason.is_empty() {
+            None
+        } else {
+            Some(reason)
+        }))
     }
 }
@@ -1,24 +1,262 @@
 use crate::{
     criterion::Criterion,
     direction::Direction,
+    expression::Expression,
     ffi::{helper, hook},
     parameter::Profile,
     utils::scaling::{Real, Usize},
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, PartialEq, Eq)]
+#[derive(Deserialize, PartialEq, Eq, Default)]
 pub(crate) enum StopAction {
     SaveState,
+    #[default]
     Terminate,
 }
 
-impl Default for StopAction {
-    fn default() -> Self {
-        Self::Terminate
+#[derive(Deserialize, Default)]
+pub(crate) enum Strategy {
+    #[default]
+    Genetic,
+    Random(RandomSearch),
+    Annealing(Annealing),
+    Tpe(Tpe),
+    Exhaustive(Exhaustive),
+    Islands(Islands),
+    Differential(Differential),
+    Hyperband(Hyperband),
+}
+
+#[derive(Deserialize)]
+pub(crate) enum Budget {
+    Evaluations(usize),
+    // seconds
+    Time(f64),
+}
+
+impl Budget {
+    // number of evaluations still allowed, at most `batch`
+    pub(crate) fn remaining(&self, batch: usize, evaluated: usize, elapsed: f64) -> usize {
+        match self {
+            Budget::Evaluations(limit) => batch.min(limit.saturating_sub(evaluated)),
+            Budget::Time(limit) if elapsed >= *limit => 0,
+            Budget::Time(_) => batch,
+        }
+    }
+
+    pub(crate) fn progress(&self, evaluated: usize, elapsed: f64) -> String {
+        match self {
+            Budget::Evaluations(limit) => format!("{}/{}", evaluated, limit),
+            Budget::Time(limit) => format!("{:.0}/{:.0}s", elapsed, limit),
+        }
     }
 }
 
+#[derive(Deserialize)]
+pub(crate) struct RandomSearch {
+    pub(crate) budget: Budget,
+    #[serde(default = "default_batch")]
+    pub(crate) batch: usize,
+}
+
+fn default_batch() -> usize {
+    64
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Annealing {
+    #[serde(default)]
+    pub(crate) initial: Option<String>,
+    pub(crate) temperature: Real,
+    // neighbours evaluated per step; the best one is the candidate move
+    #[serde(default = "default_neighbours")]
+    pub(crate) neighbours: usize,
+    // restart from a random individual after this many steps without improvement
+    #[serde(default)]
+    pub(crate) restart: Option<usize>,
+    #[serde(default)]
+    pub(crate) terminate: Termination,
+    pub(crate) mutate: Mutation,
+}
+
+fn default_neighbours() -> usize {
+    1
+}
+
+// Tree-structured Parzen estimator
+#[derive(Deserialize)]
+pub(crate) struct Tpe {
+    pub(crate) budget: Budget,
+    // random evaluations before the model is used
+    #[serde(default = "default_startup")]
+    pub(crate) startup: usize,
+    #[serde(default = "default_tpe_batch")]
+    pub(crate) batch: usize,
+    // candidates drawn per proposal
+    #[serde(default = "default_samples")]
+    pub(crate) samples: usize,
+    // fraction of observations considered good
+    #[serde(default = "default_gamma")]
+    pub(crate) gamma: f64,
+}
+
+fn default_startup() -> usize {
+    16
+}
+
+fn default_tpe_batch() -> usize {
+    1
+}
+
+fn default_samples() -> usize {
+    24
+}
+
+fn default_gamma() -> f64 {
+    0.25
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Islands {
+    pub(crate) count: usize,
+    // one entry per island, defaults to the shared hyperparameters
+    #[serde(default)]
+    pub(crate) hyperparameters: Vec<Hyperparameters>,
+    // migrate every `interval` generations
+    pub(crate) interval: usize,
+    #[serde(default = "default_migrants")]
+    pub(crate) migrants: usize,
+    #[serde(default)]
+    pub(crate) topology: Topology,
+}
+
+fn default_migrants() -> usize {
+    1
+}
+
+#[derive(Deserialize, Default)]
+pub(crate) enum Topology {
+    #[default]
+    Ring,
+    Full,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Exhaustive {
+    #[serde(default = "default_batch")]
+    pub(crate) batch: usize,
+    // refuse spaces with more combinations than this
+    #[serde(default = "default_combinations")]
+    pub(crate) limit: u64,
+}
+
+fn default_combinations() -> u64 {
+    1_000_000
+}
+
+// Differential evolution (DE/rand/1/bin) over the integer sequence parameters;
+// the other parameters are recombined and mutated by their spaces.
+#[derive(Deserialize)]
+pub(crate) struct Differential {
+    #[serde(default = "default_differential_population")]
+    pub(crate) population: usize,
+    // differential weight F
+    #[serde(default = "default_weight")]
+    pub(crate) weight: f64,
+    // crossover rate CR
+    #[serde(default = "default_rate")]
+    pub(crate) rate: f64,
+    #[serde(default)]
+    pub(crate) terminate: Termination,
+    #[serde(default)]
+    pub(crate) mutate: Mutation,
+}
+
+fn default_differential_population() -> usize {
+    32
+}
+
+fn default_weight() -> f64 {
+    0.5
+}
+
+fn default_rate() -> f64 {
+    0.9
+}
+
+// Successive halving over fidelities from `minimum` up to 1: each rung keeps
+// the best 1/eta of the candidates for the next, eta times higher, fidelity.
+// Hyperband runs the brackets starting at every fidelity in turn.
+#[derive(Deserialize)]
+pub(crate) struct Hyperband {
+    pub(crate) minimum: f64,
+    #[serde(default = "default_eta")]
+    pub(crate) eta: usize,
+    // rounds over all the brackets
+    #[serde(default = "default_iterations")]
+    pub(crate) iterations: usize,
+    // only the bracket starting at the lowest fidelity
+    #[serde(default)]
+    pub(crate) halving: bool,
+}
+
+fn default_eta() -> usize {
+    3
+}
+
+fn default_iterations() -> usize {
+    1
+}
+
+#[derive(Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+pub(crate) enum Isolation {
+    #[default]
+    None,
+    Process,
+}
+
+#[derive(Deserialize, Default)]
+pub(crate) struct Timeout {
+    #[serde(default)]
+    pub(crate) repetition: Option<f64>,
+    #[serde(default)]
+    pub(crate) individual: Option<f64>,
+}
+
+// Repeat measurements until the confidence interval of the criterion is
+// narrower than `width` relative to its value, taking at most `maximum` samples.
+#[derive(Deserialize)]
+pub(crate) struct Adaptive {
+    pub(crate) width: f64,
+    #[serde(default = "default_confidence")]
+    pub(crate) confidence: f64,
+    pub(crate) maximum: usize,
+}
+
+fn default_confidence() -> f64 {
+    0.95
+}
+
+// Hill-climbing from the best `top` individuals once the search has ended.
+#[derive(Deserialize)]
+pub(crate) struct Refinement {
+    pub(crate) top: usize,
+    // distance of a move in integer sequences
+    #[serde(default = "default_step")]
+    pub(crate) step: u32,
+}
+
+fn default_step() -> u32 {
+    1
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Objective {
+    pub(crate) name: String,
+    pub(crate) direction: Direction,
+}
+
 fn default_initial() -> usize {
     128
 }
@@ -45,6 +283,9 @@ pub(crate) struct Hyperparameters {
     pub(crate) initial: Option<String>,
     #[serde(default = "default_initial")]
     pub(crate) initial_population: usize,
+    // design of the initial population, unless `initial` is given
+    #[serde(default)]
+    pub(crate) sampler: Sampler,
     #[serde(default = "default_remain")]
     pub(crate) remain: usize,
     #[serde(default = "default_generate")]
@@ -56,6 +297,77 @@ pub(crate) struct Hyperparameters {
     pub(crate) terminate: Termination,
     #[serde(default)]
     pub(crate) mutate: Mutation,
+    #[serde(default)]
+    pub(crate) crossover: Crossover,
+    // applies to both parent selection and deletion
+    #[serde(default)]
+    pub(crate) selection: Selection,
+    #[serde(default)]
+    pub(crate) restart: Option<Restart>,
+    // fitness sharing among similar individuals, with a single objective
+    #[serde(default)]
+    pub(crate) sharing: Option<Sharing>,
+    #[serde(default)]
+    pub(crate) surrogate: Option<Surrogate>,
+    #[serde(default)]
+    pub(crate) bandit: Option<Bandit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub(crate) enum Sampler {
+    // independent draws
+    #[default]
+    Random,
+    LatinHypercube,
+    // with random linear scrambling and digital shift
+    Sobol,
+    // each individual the farthest from those before among `candidates` draws
+    Maximin {
+        #[serde(default = "default_maximin_candidates")]
+        candidates: usize,
+    },
+}
+
+fn default_maximin_candidates() -> usize {
+    16
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub(crate) enum Selection {
+    // stochastic universal sampling proportional to the distance from the worst
+    #[default]
+    Roulette,
+    Tournament {
+        size: usize,
+    },
+    // linear ranking
+    Rank,
+    // uniformly among the best `fraction`
+    Truncation {
+        fraction: f64,
+    },
+}
+
+impl Default for Hyperparameters {
+    fn default() -> Self {
+        Hyperparameters {
+            initial: None,
+            initial_population: default_initial(),
+            sampler: Sampler::default(),
+            remain: default_remain(),
+            generate: default_generate(),
+            delete: default_delete(),
+            infuse: default_infuse(),
+            terminate: Termination::default(),
+            mutate: Mutation::default(),
+            crossover: Crossover::default(),
+            selection: Selection::default(),
+            restart: None,
+            sharing: None,
+            surrogate: None,
+            bandit: None,
+        }
+    }
 }
 
 impl Hyperparameters {
@@ -64,9 +376,61 @@ impl Hyperparameters {
         self.delete.step();
         self.infuse.step();
         self.mutate.step();
+        self.crossover.step();
+    }
+}
+
+// Operators left unset (or not drawn) fall back to the default of each space:
+// the midpoint for sequences and a random value for differing parents otherwise.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub(crate) struct Crossover {
+    // single- or multi-point crossover across the ordered parameters
+    #[serde(default)]
+    pub(crate) point: Option<PointCrossover>,
+    // for integer sequences
+    #[serde(default)]
+    pub(crate) sequence: Option<SequenceCrossover>,
+    // inherit each parameter from either parent
+    #[serde(default)]
+    pub(crate) uniform: Option<UniformCrossover>,
+}
+
+impl Crossover {
+    pub(crate) fn step(&mut self) {
+        if let Some(point) = &mut self.point {
+            point.probability.step();
+        }
+        if let Some(sequence) = &mut self.sequence {
+            match sequence {
+                SequenceCrossover::Blend { probability, .. }
+                | SequenceCrossover::Simulated { probability, .. } => probability.step(),
+            }
+        }
+        if let Some(uniform) = &mut self.uniform {
+            uniform.probability.step();
+        }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct PointCrossover {
+    pub(crate) points: usize,
+    pub(crate) probability: Real,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) enum SequenceCrossover {
+    // BLX-alpha
+    Blend { alpha: f64, probability: Real },
+    // SBX with distribution index eta
+    Simulated { eta: f64, probability: Real },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct UniformCrossover {
+    pub(crate) probability: Real,
+}
+
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub(crate) struct Mutation {
     #[serde(default)]
@@ -92,6 +456,23 @@ impl Mutation {
             keyword.probability.step();
         }
     }
+
+    // multiplies every probability by `factor`, up to 1
+    pub(crate) fn boost(&mut self, factor: f64) {
+        let probabilities = self
+            .integer
+            .iter_mut()
+            .map(|integer| &mut integer.probability)
+            .chain(self.switch.iter_mut().map(|switch| &mut switch.probability))
+            .chain(
+                self.keyword
+                    .iter_mut()
+                    .map(|keyword| &mut keyword.probability),
+            );
+        for probability in probabilities {
+            probability.value = (probability.value * factor).min(1.0);
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -111,7 +492,116 @@ pub(crate) struct KeywordMutation {
     pub(crate) probability: Real,
 }
 
+// Individuals closer than `radius` (a profile distance, from 0 to 1) share
+// their fitness: parent selection weights are divided by the niche count and
+// deletion weights multiplied by it.
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Sharing {
+    pub(crate) radius: f64,
+    #[serde(default = "default_sharing_alpha")]
+    pub(crate) alpha: f64,
+}
+
+fn default_sharing_alpha() -> f64 {
+    1.0
+}
+
+// Adaptive operator selection: every child is bred by a single operator drawn
+// by probability matching on the rewards of its earlier children. A child
+// scores one for beating the population median and one more for a new best;
+// copies of a member of the population score nothing. Beating the median
+// stands in for entering the population, which is only settled once the whole
+// generation is bred and may take several batches.
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Bandit {
+    // mutation of the large-variation operator
+    pub(crate) large: Mutation,
+    // lower bound of the probability of each operator
+    #[serde(default = "default_minimum_probability")]
+    pub(crate) minimum: f64,
+    // rate at which the operator qualities follow the rewards
+    #[serde(default = "default_adaptation")]
+    pub(crate) adaptation: f64,
+}
+
+fn default_minimum_probability() -> f64 {
+    0.05
+}
+
+fn default_adaptation() -> f64 {
+    0.3
+}
+
+// Estimates the fitness of children from the `neighbours` nearest individuals
+// evaluated so far. Children predicted worse than the `quantile` of the
+// population are bred again before compilation; after `rounds` the best
+// predicted of them fill the remaining places.
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Surrogate {
+    #[serde(default = "default_surrogate_neighbours")]
+    pub(crate) neighbours: usize,
+    #[serde(default = "default_quantile")]
+    pub(crate) quantile: f64,
+    // evaluated individuals required before screening
+    #[serde(default = "default_warmup")]
+    pub(crate) warmup: usize,
+    #[serde(default = "default_rounds")]
+    pub(crate) rounds: usize,
+}
+
+fn default_surrogate_neighbours() -> usize {
+    5
+}
+
+fn default_quantile() -> f64 {
+    1.0
+}
+
+fn default_warmup() -> usize {
+    32
+}
+
+fn default_rounds() -> usize {
+    3
+}
+
+// Re-diversifies the population once the best has not improved for
+// `stagnation` generations, before `endure` of the termination applies.
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Restart {
+    pub(crate) stagnation: usize,
+    // number of the best individuals kept as they are
+    #[serde(default = "default_restart_elite")]
+    pub(crate) elite: usize,
+    #[serde(default)]
+    pub(crate) reseed: Reseed,
+    #[serde(default)]
+    pub(crate) boost: Option<Boost>,
+    // maximum number of restarts
+    #[serde(default)]
+    pub(crate) limit: Option<usize>,
+}
+
+fn default_restart_elite() -> usize {
+    1
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub(crate) enum Reseed {
+    #[default]
+    Random,
+    // copies of the elite mutated with these settings
+    Mutated(Mutation),
+}
+
+// Temporarily multiplies the mutation probabilities after a restart.
 #[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Boost {
+    pub(crate) factor: f64,
+    pub(crate) generations: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub(crate) struct Termination {
     #[serde(default)]
     pub(crate) goal: Option<f64>,
@@ -121,14 +611,37 @@ pub(crate) struct Termination {
     pub(crate) endure: Option<usize>,
 }
 
+impl Termination {
+    // whether Progress::terminate would stop after the `generation`-th generation
+    pub(crate) fn is_reached(&self, generation: usize, count: usize) -> bool {
+        self.limit.is_some_and(|limit| generation >= limit)
+            || (self.goal.is_none() && self.endure.is_some_and(|endure| count >= endure))
+    }
+}
+
 #[derive(Deserialize)]
 pub(crate) struct Configuration {
     #[serde(default)]
     pub(crate) unit: Option<String>,
     pub(crate) direction: Direction,
+    #[serde(default)]
+    pub(crate) objectives: Vec<Objective>,
     pub(crate) criterion: Criterion,
     #[serde(default)]
+    pub(crate) fitness: Option<Expression>,
+    #[serde(default)]
     pub(crate) stop_action: StopAction,
+    #[serde(default)]
+    pub(crate) isolation: Isolation,
+    #[serde(default)]
+    pub(crate) timeout: Timeout,
+    #[serde(default)]
+    pub(crate) adaptive: Option<Adaptive>,
+    #[serde(default)]
+    pub(crate) refinement: Option<Refinement>,
+    #[serde(default)]
+    pub(crate) strategy: Strategy,
+    #[serde(default)]
     pub(crate) hyperparameters: Hyperparameters,
     pub(crate) profile: Profile,
     pub(crate) helper: helper::Configuration,
@@ -1,3 +1,4 @@
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy)]
@@ -48,3 +49,73 @@ impl Criterion {
         }
     }
 }
+
+impl Criterion {
+    // Confidence interval of the criterion over the samples: order statistics
+    // for the median, a percentile bootstrap for the extrema.
+    pub(crate) fn interval(&self, values: &[f64], confidence: f64) -> Option<(f64, f64)> {
+        let n = values.len();
+        if n < 2 {
+            return None;
+        }
+        let alpha = (1.0 - confidence) / 2.0;
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        match self {
+            Criterion::Median => {
+                // the number of samples below the median follows Binomial(n, 1/2)
+                let mut probability = 0.5f64.powi(n as i32);
+                let mut cumulative = probability;
+                let mut lower = 0;
+                while cumulative <= alpha {
+                    lower += 1;
+                    probability *= (n - lower + 1) as f64 / lower as f64;
+                    cumulative += probability;
+                }
+                if lower == 0 {
+                    return None;
+                }
+                Some((sorted[lower - 1], sorted[n - lower]))
+            }
+            Criterion::Maximum | Criterion::Minimum => {
+                const RESAMPLES: usize = 1000;
+                let mut rng = rand::rng();
+                let mut statistics = (0..RESAMPLES)
+                    .map(|_| {
+                        let resample = (0..n).map(|_| sorted[rng.random_range(0..n)]);
+                        match self {
+                            Criterion::Maximum => resample.fold(f64::NEG_INFINITY, f64::max),
+                            _ => resample.fold(f64::INFINITY, f64::min),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                statistics.sort_by(|a, b| a.total_cmp(b));
+                let lower = (alpha * RESAMPLES as f64).floor() as usize;
+                let upper = ((1.0 - alpha) * RESAMPLES as f64).ceil() as usize;
+                Some((statistics[lower], statistics[upper.min(RESAMPLES) - 1]))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_interval() {
+        let values = (1..=10).map(f64::from).collect::<Vec<_>>();
+        // P(B <= 1) = 11/1024 <= 0.025 < P(B <= 2) for B ~ Binomial(10, 1/2)
+        assert_eq!(Criterion::Median.interval(&values, 0.95), Some((2.0, 9.0)));
+        assert_eq!(Criterion::Median.interval(&values[..5], 0.95), None);
+    }
+
+    #[test]
+    fn test_bootstrap_interval() {
+        let values = [3.0; 8];
+        assert_eq!(Criterion::Maximum.interval(&values, 0.95), Some((3.0, 3.0)));
+        let values = (1..=20).map(f64::from).collect::<Vec<_>>();
+        let (lower, upper) = Criterion::Minimum.interval(&values, 0.95).unwrap();
+        assert!(lower >= 1.0 && lower <= upper && upper <= 20.0);
+    }
+}
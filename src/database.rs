@@ -0,0 +1,148 @@
+use crate::individual::{Individual, Sample};
+use argh::FromArgValue;
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi, fs,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Policy {
+    Reuse,
+    Remeasure,
+    Blend,
+}
+
+impl FromArgValue for Policy {
+    fn from_arg_value(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "reuse" => Ok(Policy::Reuse),
+            "remeasure" => Ok(Policy::Remeasure),
+            "blend" => Ok(Policy::Blend),
+            _ => Err(format!("Invalid database policy: {}", value)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    id: Arc<str>,
+    environment: Arc<str>,
+    parameters: String,
+    #[serde(default = "full", skip_serializing_if = "is_full")]
+    fidelity: f64,
+    samples: Vec<Sample>,
+}
+
+fn full() -> f64 {
+    1.0
+}
+
+fn is_full(fidelity: &f64) -> bool {
+    *fidelity == 1.0
+}
+
+// records are kept per fidelity
+type Key = (Arc<str>, u64);
+
+fn key(individual: &Individual) -> Key {
+    (individual.id.clone(), individual.fidelity.to_bits())
+}
+
+// Append-only JSONL store of measurements. Only records taken in the same
+// environment (see `Autotuner::new`) are loaded; the latest record of an
+// individual wins.
+pub(crate) struct Database {
+    policy: Policy,
+    environment: Arc<str>,
+    records: Mutex<FxHashMap<Key, Vec<Sample>>>,
+    file: Mutex<fs::File>,
+}
+
+impl Database {
+    pub(crate) fn open<P: AsRef<Path>>(
+        path: P,
+        policy: Policy,
+        environment: String,
+    ) -> io::Result<Self> {
+        let environment: Arc<str> = environment.into();
+        let mut records = FxHashMap::default();
+        if path.as_ref().exists() {
+            let reader = BufReader::new(fs::File::open(&path)?);
+            for line in reader.lines() {
+                let record = match serde_json::from_str::<Record>(&line?) {
+                    Ok(record) => record,
+                    Err(_) => continue,
+                };
+                if record.environment == environment {
+                    records.insert((record.id, record.fidelity.to_bits()), record.samples);
+                }
+            }
+        }
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        Ok(Database {
+            policy,
+            environment,
+            records: Mutex::new(records),
+            file: Mutex::new(file),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    pub(crate) fn get(&self, individual: &Individual) -> Option<Vec<Sample>> {
+        self.records.lock().unwrap().get(&key(individual)).cloned()
+    }
+
+    pub(crate) fn insert(&self, individual: &Individual, parameters: String) -> io::Result<()> {
+        let record = Record {
+            id: individual.id.clone(),
+            environment: self.environment.clone(),
+            parameters,
+            fidelity: individual.fidelity,
+            samples: individual.samples.clone(),
+        };
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        self.file.lock().unwrap().write_all(&line)?;
+        self.records
+            .lock()
+            .unwrap()
+            .insert(key(individual), record.samples);
+        Ok(())
+    }
+}
+
+// Host name and CPU model of the current machine, as part of the environment.
+pub(crate) fn machine() -> [String; 2] {
+    let mut buffer = [0u8; 256];
+    let hostname = if unsafe { libc::gethostname(buffer.as_mut_ptr() as _, buffer.len()) } == 0 {
+        ffi::CStr::from_bytes_until_nul(&buffer)
+            .map(|hostname| hostname.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    } else {
+        String::new()
+    };
+    let cpu = fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|cpuinfo| {
+            cpuinfo
+                .lines()
+                .find(|line| line.starts_with("model name"))
+                .and_then(|line| line.split_once(':'))
+                .map(|(_, model)| model.trim().to_string())
+        })
+        .unwrap_or_default();
+    [hostname, cpu]
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::cmp;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Direction {
     Minimize,
     Maximize,
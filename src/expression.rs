@@ -0,0 +1,355 @@
+use serde::{Deserialize, Deserializer};
+use std::{fmt, iter::Peekable, str::Chars};
+
+// Arithmetic expression over named metrics, e.g. `gflops / energy`.
+// Supports + - * / ^, parentheses, numeric literals and the functions
+// min, max, abs, sqrt, log and exp.
+pub(crate) enum Expression {
+    Constant(f64),
+    Metric(String),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Function {
+    Min,
+    Max,
+    Abs,
+    Sqrt,
+    Log,
+    Exp,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            "abs" => Some(Function::Abs),
+            "sqrt" => Some(Function::Sqrt),
+            "log" => Some(Function::Log),
+            "exp" => Some(Function::Exp),
+            _ => None,
+        }
+    }
+
+    fn arity(&self) -> Option<usize> {
+        match self {
+            Function::Min | Function::Max => None,
+            _ => Some(1),
+        }
+    }
+}
+
+impl Function {
+    fn name(&self) -> &'static str {
+        match self {
+            Function::Min => "min",
+            Function::Max => "max",
+            Function::Abs => "abs",
+            Function::Sqrt => "sqrt",
+            Function::Log => "log",
+            Function::Exp => "exp",
+        }
+    }
+}
+
+// Fully parenthesized, so that the result parses back to the same expression.
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Constant(x) => write!(f, "{}", x),
+            Expression::Metric(name) => write!(f, "{}", name),
+            Expression::Negate(e) => write!(f, "(-{})", e),
+            Expression::Binary(operator, a, b) => {
+                let symbol = match operator {
+                    Operator::Add => '+',
+                    Operator::Subtract => '-',
+                    Operator::Multiply => '*',
+                    Operator::Divide => '/',
+                    Operator::Power => '^',
+                };
+                write!(f, "({} {} {})", a, symbol, b)
+            }
+            Expression::Call(function, arguments) => {
+                write!(f, "{}(", function.name())?;
+                for (i, argument) in arguments.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", argument)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Error(String);
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid expression: {}", self.0)
+    }
+}
+
+impl Expression {
+    pub(crate) fn parse(s: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            chars: s.chars().peekable(),
+        };
+        let expression = parser.expression()?;
+        parser.skip_whitespace();
+        if let Some(c) = parser.chars.next() {
+            return Err(Error(format!("unexpected '{}'", c)));
+        }
+        Ok(expression)
+    }
+
+    // Returns None if a referenced metric is missing.
+    pub(crate) fn evaluate(&self, metric: &dyn Fn(&str) -> Option<f64>) -> Option<f64> {
+        match self {
+            Expression::Constant(x) => Some(*x),
+            Expression::Metric(name) => metric(name),
+            Expression::Negate(e) => Some(-e.evaluate(metric)?),
+            Expression::Binary(operator, a, b) => {
+                let (a, b) = (a.evaluate(metric)?, b.evaluate(metric)?);
+                Some(match operator {
+                    Operator::Add => a + b,
+                    Operator::Subtract => a - b,
+                    Operator::Multiply => a * b,
+                    Operator::Divide => a / b,
+                    Operator::Power => a.powf(b),
+                })
+            }
+            Expression::Call(function, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(metric))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(match function {
+                    Function::Min => arguments.into_iter().fold(f64::INFINITY, f64::min),
+                    Function::Max => arguments.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                    Function::Abs => arguments[0].abs(),
+                    Function::Sqrt => arguments[0].sqrt(),
+                    Function::Log => arguments[0].ln(),
+                    Function::Exp => arguments[0].exp(),
+                })
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Expression {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Expression::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().copied()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            Some(c) => Err(Error(format!("expected '{}', found '{}'", expected, c))),
+            None => Err(Error(format!("expected '{}'", expected))),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, Error> {
+        let mut lhs = self.term()?;
+        loop {
+            let operator = match self.peek() {
+                Some('+') => Operator::Add,
+                Some('-') => Operator::Subtract,
+                _ => return Ok(lhs),
+            };
+            self.chars.next();
+            let rhs = self.term()?;
+            lhs = Expression::Binary(operator, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expression, Error> {
+        let mut lhs = self.unary()?;
+        loop {
+            let operator = match self.peek() {
+                Some('*') => Operator::Multiply,
+                Some('/') => Operator::Divide,
+                _ => return Ok(lhs),
+            };
+            self.chars.next();
+            let rhs = self.unary()?;
+            lhs = Expression::Binary(operator, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression, Error> {
+        if self.peek() == Some('-') {
+            self.chars.next();
+            return Ok(Expression::Negate(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expression, Error> {
+        let base = self.atom()?;
+        if self.peek() == Some('^') {
+            self.chars.next();
+            let exponent = self.unary()?;
+            return Ok(Expression::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expression, Error> {
+        match self.peek() {
+            Some('(') => {
+                self.chars.next();
+                let expression = self.expression()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let mut literal = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    literal.push(c);
+                }
+                if let Some(e) = self.chars.next_if(|c| *c == 'e' || *c == 'E') {
+                    literal.push(e);
+                    if let Some(sign) = self.chars.next_if(|c| *c == '+' || *c == '-') {
+                        literal.push(sign);
+                    }
+                    while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit()) {
+                        literal.push(c);
+                    }
+                }
+                literal
+                    .parse()
+                    .map(Expression::Constant)
+                    .map_err(|_| Error(format!("invalid number '{}'", literal)))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(c) = self
+                    .chars
+                    .next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '.')
+                {
+                    name.push(c);
+                }
+                if self.peek() != Some('(') {
+                    return Ok(Expression::Metric(name));
+                }
+
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| Error(format!("unknown function '{}'", name)))?;
+                self.chars.next();
+                let mut arguments = Vec::new();
+                if self.peek() != Some(')') {
+                    loop {
+                        arguments.push(self.expression()?);
+                        if self.peek() != Some(',') {
+                            break;
+                        }
+                        self.chars.next();
+                    }
+                }
+                self.expect(')')?;
+                match function.arity() {
+                    Some(arity) if arity != arguments.len() => {
+                        Err(Error(format!("'{}' takes {} argument(s)", name, arity)))
+                    }
+                    None if arguments.is_empty() => {
+                        Err(Error(format!("'{}' takes at least one argument", name)))
+                    }
+                    _ => Ok(Expression::Call(function, arguments)),
+                }
+            }
+            Some(c) => Err(Error(format!("unexpected '{}'", c))),
+            None => Err(Error("unexpected end of expression".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(s: &str) -> Option<f64> {
+        let expression = Expression::parse(s).unwrap();
+        expression.evaluate(&|name| match name {
+            "gflops" => Some(120.0),
+            "energy" => Some(4.0),
+            "elapsed.ns" => Some(2e6),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("gflops / energy"), Some(30.0));
+        assert_eq!(evaluate("1 + 2 * 3"), Some(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3"), Some(9.0));
+        assert_eq!(evaluate("-2 ^ 2"), Some(-4.0));
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), Some(512.0));
+        assert_eq!(evaluate("elapsed.ns / 1e6"), Some(2.0));
+        assert_eq!(evaluate("max(gflops, energy, 1000)"), Some(1000.0));
+        assert_eq!(evaluate("sqrt(energy)"), Some(2.0));
+        assert_eq!(evaluate("gflops / bytes"), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let expression = Expression::parse("-2 ^ 2 + max(gflops, 1e-3) / energy").unwrap();
+        let displayed = expression.to_string();
+        assert_eq!(displayed, "((-(2 ^ 2)) + (max(gflops, 0.001) / energy))");
+        let reparsed = Expression::parse(&displayed).unwrap();
+        assert_eq!(reparsed.to_string(), displayed);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(Expression::parse("gflops /").is_err());
+        assert!(Expression::parse("(gflops").is_err());
+        assert!(Expression::parse("gflops energy").is_err());
+        assert!(Expression::parse("foo(1)").is_err());
+        assert!(Expression::parse("sqrt(1, 2)").is_err());
+    }
+}
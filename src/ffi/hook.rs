@@ -8,21 +8,12 @@ use libloading::Symbol;
 use serde::{Deserialize, Serialize};
 use std::{ffi, ptr};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub(crate) struct Configuration {
     pub(crate) pre: Vec<String>,
     pub(crate) post: Vec<String>,
 }
 
-impl Default for Configuration {
-    fn default() -> Self {
-        Self {
-            pre: Vec::new(),
-            post: Vec::new(),
-        }
-    }
-}
-
 type Function = unsafe extern "C" fn(
     ctx: *mut Context,
     get: extern "C" fn(id: ffi::c_int) -> *const ffi::c_void,
@@ -1,4 +1,4 @@
-use crate::ffi::context::Context;
+use crate::{ffi::context::Context, utils::interner::Intern};
 use libloading::Symbol;
 use std::{ffi, ptr};
 
@@ -26,8 +26,11 @@ impl<'a> Runner<'a> {
 #[repr(u32)]
 enum Interface {
     GetPtr = 0x00,
+    GetFidelity = 0x01,
 
     SetResult = 0x10,
+    SetObjective = 0x11,
+    SetMetric = 0x12,
 }
 
 impl TryFrom<ffi::c_int> for Interface {
@@ -36,7 +39,10 @@ impl TryFrom<ffi::c_int> for Interface {
     fn try_from(value: ffi::c_int) -> Result<Self, Self::Error> {
         match value {
             x if x == Interface::GetPtr as ffi::c_int => Ok(Interface::GetPtr),
+            x if x == Interface::GetFidelity as ffi::c_int => Ok(Interface::GetFidelity),
             x if x == Interface::SetResult as ffi::c_int => Ok(Interface::SetResult),
+            x if x == Interface::SetObjective as ffi::c_int => Ok(Interface::SetObjective),
+            x if x == Interface::SetMetric as ffi::c_int => Ok(Interface::SetMetric),
             _ => Err(()),
         }
     }
@@ -45,7 +51,10 @@ impl TryFrom<ffi::c_int> for Interface {
 extern "C" fn get(id: ffi::c_int) -> *const ffi::c_void {
     match Interface::try_from(id) {
         Ok(Interface::GetPtr) => get_ptr as *const ffi::c_void,
+        Ok(Interface::GetFidelity) => get_fidelity as *const ffi::c_void,
         Ok(Interface::SetResult) => set_result as *const ffi::c_void,
+        Ok(Interface::SetObjective) => set_objective as *const ffi::c_void,
+        Ok(Interface::SetMetric) => set_metric as *const ffi::c_void,
         _ => ptr::null(),
     }
 }
@@ -68,6 +77,14 @@ extern "C" fn get_ptr(ctx: *mut Context, name: *const ffi::c_char) -> *const *mu
     }
 }
 
+extern "C" fn get_fidelity(ctx: *mut Context) -> f64 {
+    if let Some(ctx) = unsafe { ctx.as_ref() } {
+        ctx.individual.fidelity
+    } else {
+        1.0
+    }
+}
+
 extern "C" fn set_result(ctx: *mut Context, result: f64) {
     let ctx = if let Some(ctx) = unsafe { ctx.as_mut() } {
         ctx
@@ -76,3 +93,33 @@ extern "C" fn set_result(ctx: *mut Context, result: f64) {
     };
     ctx.individual.fitness = crate::individual::Fitness::Valid(result);
 }
+
+extern "C" fn set_objective(ctx: *mut Context, index: ffi::c_int, value: f64) {
+    let ctx = if let Some(ctx) = unsafe { ctx.as_mut() } {
+        ctx
+    } else {
+        return;
+    };
+    if let Some(objective) = usize::try_from(index)
+        .ok()
+        .and_then(|index| ctx.individual.objectives.get_mut(index))
+    {
+        *objective = crate::individual::Fitness::Valid(value);
+    }
+}
+
+extern "C" fn set_metric(ctx: *mut Context, name: *const ffi::c_char, value: f64) {
+    let ctx = if let Some(ctx) = unsafe { ctx.as_mut() } {
+        ctx
+    } else {
+        return;
+    };
+    let name = if let Some(name) = unsafe { ffi::CStr::from_ptr(name).to_str().ok() } {
+        name
+    } else {
+        return;
+    };
+    ctx.individual
+        .metrics
+        .insert(name.intern(), crate::individual::Fitness::Valid(value));
+}
@@ -1,9 +1,15 @@
-use crate::configuration::Mutation;
+use crate::configuration::{Crossover, Mutation, Selection, SequenceCrossover, Sharing};
+use crate::direction::Direction;
 use crate::individual::Individual;
-use crate::parameter::{IntoJson, Profile};
-use rayon::iter::{IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
+use crate::parameter::{Combination, IntoJson, Profile, Specification, space};
+use rand::seq::{IndexedRandom, SliceRandom};
+use rayon::iter::{
+    IntoParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
 use serde::Serialize;
+use std::cmp;
 use std::collections::{BTreeMap, HashSet};
+use std::fmt::Display;
 use std::io;
 use std::time::SystemTime;
 
@@ -13,6 +19,16 @@ pub(crate) struct GenerationSummary {
     pub(crate) global_best: Individual,
     pub(crate) current_best: f64,
     pub(crate) current_worst: f64,
+    // fidelity of the evaluations, when not the full one
+    pub(crate) fidelity: Option<f64>,
+    // children rejected by the surrogate while breeding this population
+    pub(crate) screened: Option<usize>,
+    // statistics of the children bred for this population by each operator
+    pub(crate) operators: Option<Vec<(Operator, Statistics)>>,
+    // mean pairwise distance of the population
+    pub(crate) diversity: Option<f64>,
+    // number of the restart performed after this generation
+    pub(crate) restart: Option<usize>,
 }
 
 impl GenerationSummary {
@@ -21,6 +37,27 @@ impl GenerationSummary {
         writeln!(file, "Best overall: {} {}", self.global_best.fitness, unit)?;
         writeln!(file, "Best: {} {}", self.current_best, unit)?;
         writeln!(file, "Worst: {} {}", self.current_worst, unit)?;
+        if let Some(fidelity) = self.fidelity {
+            writeln!(file, "Fidelity: {}", fidelity)?;
+        }
+        if let Some(screened) = self.screened {
+            writeln!(file, "Screened out: {}", screened)?;
+        }
+        if let Some(operators) = &self.operators {
+            for (operator, statistics) in operators {
+                writeln!(
+                    file,
+                    "{}: {}/{} successful, {} improved",
+                    operator, statistics.successes, statistics.children, statistics.improvements
+                )?;
+            }
+        }
+        if let Some(diversity) = self.diversity {
+            writeln!(file, "Diversity: {:.4}", diversity)?;
+        }
+        if let Some(restart) = self.restart {
+            writeln!(file, "Restart #{}", restart)?;
+        }
         Ok(())
     }
 }
@@ -37,6 +74,11 @@ impl GenerationSummary {
             global_best,
             current_best,
             current_worst,
+            fidelity: None,
+            screened: None,
+            operators: None,
+            diversity: None,
+            restart: None,
         }
     }
 }
@@ -52,6 +94,13 @@ impl IntoJson for GenerationSummary {
             "global_best".to_string(),
             serde_json::Value::String(profile.individual_to_string(&self.global_best)),
         );
+        let metrics = self.global_best.metric_values();
+        if !metrics.is_empty() {
+            serialized.insert(
+                "global_best_metrics".to_string(),
+                serde_json::to_value(metrics).unwrap(),
+            );
+        }
         serialized.insert(
             "current_best".to_string(),
             serde_json::Value::Number(serde_json::Number::from_f64(self.current_best).unwrap()),
@@ -60,6 +109,45 @@ impl IntoJson for GenerationSummary {
             "current_worst".to_string(),
             serde_json::Value::Number(serde_json::Number::from_f64(self.current_worst).unwrap()),
         );
+        if let Some(fidelity) = self.fidelity {
+            serialized.insert(
+                "fidelity".to_string(),
+                serde_json::Value::Number(serde_json::Number::from_f64(fidelity).unwrap()),
+            );
+        }
+        if let Some(screened) = self.screened {
+            serialized.insert(
+                "screened".to_string(),
+                serde_json::Value::Number(screened.into()),
+            );
+        }
+        if let Some(operators) = self.operators {
+            let operators = operators
+                .into_iter()
+                .map(|(operator, statistics)| {
+                    (
+                        operator.to_string(),
+                        serde_json::to_value(statistics).unwrap(),
+                    )
+                })
+                .collect();
+            serialized.insert(
+                "operators".to_string(),
+                serde_json::Value::Object(operators),
+            );
+        }
+        if let Some(diversity) = self.diversity {
+            serialized.insert(
+                "diversity".to_string(),
+                serde_json::Value::Number(serde_json::Number::from_f64(diversity).unwrap()),
+            );
+        }
+        if let Some(restart) = self.restart {
+            serialized.insert(
+                "restart".to_string(),
+                serde_json::Value::Number(restart.into()),
+            );
+        }
         serde_json::Value::Object(serialized)
     }
 }
@@ -74,27 +162,213 @@ impl IntoJson for Vec<GenerationSummary> {
     }
 }
 
-pub(crate) fn crossover(profile: &Profile, a: &Individual, b: &Individual) -> Individual {
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operator {
+    Crossover,
+    Mutation,
+    Large,
+    Immigrant,
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operator::Crossover => write!(f, "Crossover"),
+            Operator::Mutation => write!(f, "Mutation"),
+            Operator::Large => write!(f, "Large mutation"),
+            Operator::Immigrant => write!(f, "Immigrant"),
+        }
+    }
+}
+
+impl Operator {
+    pub(crate) const ALL: [Operator; 4] = [
+        Operator::Crossover,
+        Operator::Mutation,
+        Operator::Large,
+        Operator::Immigrant,
+    ];
+
+    // Probability matching: each operator gets `minimum` and shares the rest
+    // in proportion to its quality.
+    pub(crate) fn probabilities(qualities: &[f64], minimum: f64) -> Vec<f64> {
+        let n = Self::ALL.len();
+        let sum = qualities.iter().sum::<f64>();
+        if qualities.len() != n || sum <= 0.0 {
+            return vec![1.0 / n as f64; n];
+        }
+        qualities
+            .iter()
+            .map(|quality| minimum + (1.0 - n as f64 * minimum) * quality / sum)
+            .collect()
+    }
+
+    pub(crate) fn draw(probabilities: &[f64]) -> Operator {
+        let mut x = rand::random::<f64>();
+        for (operator, probability) in Self::ALL.iter().zip(probabilities) {
+            if x < *probability {
+                return *operator;
+            }
+            x -= probability;
+        }
+        Operator::Immigrant
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Default)]
+pub(crate) struct Statistics {
+    pub(crate) children: usize,
+    // valid children better than the median of the population
+    pub(crate) successes: usize,
+    // children better than the best so far
+    pub(crate) improvements: usize,
+}
+
+impl Statistics {
+    pub(crate) fn reward(&self) -> f64 {
+        if self.children == 0 {
+            0.0
+        } else {
+            (self.successes + self.improvements) as f64 / self.children as f64
+        }
+    }
+}
+
+// mean distance over all pairs of the population
+pub(crate) fn diversity(profile: &Profile, population: &[Individual]) -> f64 {
+    let n = population.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let sum = (0..n)
+        .into_par_iter()
+        .map(|i| {
+            (i + 1..n)
+                .map(|j| profile.distance(&population[i].parameters, &population[j].parameters))
+                .sum::<f64>()
+        })
+        .sum::<f64>();
+    sum / (n * (n - 1) / 2) as f64
+}
+
+// niche count of each individual: the sum of the sharing function over the
+// population, including itself
+pub(crate) fn niche_counts(
+    profile: &Profile,
+    sharing: &Sharing,
+    population: &[Individual],
+) -> Vec<f64> {
+    population
+        .par_iter()
+        .map(|a| {
+            population
+                .iter()
+                .map(|b| profile.distance(&a.parameters, &b.parameters))
+                .filter(|distance| *distance < sharing.radius)
+                .map(|distance| 1.0 - (distance / sharing.radius).powf(sharing.alpha))
+                .sum()
+        })
+        .collect()
+}
+
+// Inverse-distance weighted mean fitness of the `k` nearest samples.
+pub(crate) fn predict(
+    profile: &Profile,
+    samples: &[(&Combination, f64)],
+    k: usize,
+    parameters: &Combination,
+) -> Option<f64> {
+    let mut nearest = samples
+        .iter()
+        .map(|(other, fitness)| (profile.distance(parameters, other), *fitness))
+        .collect::<Vec<_>>();
+    nearest.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    nearest.truncate(k);
+    if let Some((distance, fitness)) = nearest.first()
+        && *distance == 0.0
+    {
+        return Some(*fitness);
+    }
+    let weight = nearest
+        .iter()
+        .map(|(distance, _)| 1.0 / distance)
+        .sum::<f64>();
+    let sum = nearest
+        .iter()
+        .map(|(distance, fitness)| fitness / distance)
+        .sum::<f64>();
+    (weight > 0.0).then(|| sum / weight)
+}
+
+pub(crate) fn crossover(
+    profile: &Profile,
+    options: &Crossover,
+    a: &Individual,
+    b: &Individual,
+) -> Individual {
+    if let Some(point) = &options.point
+        && rand::random_bool(point.probability.value)
+    {
+        // alternate between the parents at randomly chosen cut points
+        let length = a.parameters.len();
+        let gaps = length.saturating_sub(1);
+        let cuts = rand::seq::index::sample(&mut rand::rng(), gaps, point.points.min(gaps))
+            .into_iter()
+            .map(|cut| cut + 1)
+            .collect::<HashSet<_>>();
+        let mut from_a = true;
+        let parameters = a
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(position, (name, value))| {
+                if cuts.contains(&position) {
+                    from_a = !from_a;
+                }
+                let value = if from_a { *value } else { b.parameters[name] };
+                (name.clone(), value)
+            })
+            .collect();
+        return Individual::new(parameters);
+    }
+
     let parameters = a
         .parameters
         .par_iter()
-        .fold(
-            || BTreeMap::new(),
-            |mut parameters, parameter| {
-                let specification = profile.0.get(parameter.0).unwrap();
-                let space = specification.get_space();
-                let value = space.crossover(a.parameters[parameter.0], b.parameters[parameter.0]);
-                parameters.insert(parameter.0.clone(), value);
-                parameters
-            },
-        )
-        .reduce(
-            || BTreeMap::new(),
-            |mut acc, parameters| {
-                acc.extend(parameters);
-                acc
-            },
-        );
+        .map(|(name, &x)| {
+            let y = b.parameters[name];
+            let specification = profile.0.get(name).unwrap();
+            let value = match (specification.as_ref(), &options.sequence) {
+                (
+                    Specification::Integer {
+                        space: space @ space::Integer::Sequence(_, _),
+                        ..
+                    },
+                    Some(SequenceCrossover::Blend { alpha, probability }),
+                ) if rand::random_bool(probability.value) => space.blend(x, y, *alpha),
+                (
+                    Specification::Integer {
+                        space: space @ space::Integer::Sequence(_, _),
+                        ..
+                    },
+                    Some(SequenceCrossover::Simulated { eta, probability }),
+                ) if rand::random_bool(probability.value) => space.simulated_binary(x, y, *eta),
+                _ if options
+                    .uniform
+                    .as_ref()
+                    .is_some_and(|uniform| rand::random_bool(uniform.probability.value)) =>
+                {
+                    if rand::random() {
+                        x
+                    } else {
+                        y
+                    }
+                }
+                _ => specification.get_space().crossover(x, y),
+            };
+            (name.clone(), value)
+        })
+        .collect::<BTreeMap<_, _>>();
     Individual::new(parameters)
 }
 
@@ -155,3 +429,367 @@ pub(crate) fn stochastic_universal_sampling(
 
     selected
 }
+
+// Picks `n` entries preferring larger weights, with or without repetition.
+// With repetition, entries of weight zero are never picked, as with the
+// roulette, unless all weights are zero.
+pub(crate) fn select(
+    selection: &Selection,
+    weights: &[(f64, usize)],
+    n: usize,
+    unique: bool,
+) -> Vec<usize> {
+    assert!(!weights.is_empty());
+    if unique {
+        assert!(n <= weights.len());
+    }
+
+    let positive;
+    let weights = if unique {
+        weights
+    } else {
+        positive = weights
+            .iter()
+            .copied()
+            .filter(|(weight, _)| *weight > 0.0)
+            .collect::<Vec<_>>();
+        if positive.is_empty() {
+            weights
+        } else {
+            &positive
+        }
+    };
+
+    let mut rng = rand::rng();
+    match selection {
+        Selection::Roulette => stochastic_universal_sampling(weights, n, unique),
+        Selection::Tournament { size } => {
+            let mut pool = weights.to_vec();
+            let mut selected = Vec::with_capacity(n);
+            for _ in 0..n {
+                pool.shuffle(&mut rng);
+                let size = (*size).clamp(1, pool.len());
+                let (position, _) = pool[..size]
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| a.0.total_cmp(&b.0))
+                    .unwrap();
+                selected.push(pool[position].1);
+                if unique {
+                    pool.swap_remove(position);
+                }
+            }
+            selected
+        }
+        Selection::Rank => {
+            // linear ranking: the i-th worst entry gets weight i
+            let mut ranked = weights.to_vec();
+            ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let ranked = ranked
+                .into_iter()
+                .enumerate()
+                .map(|(rank, (_, index))| ((rank + 1) as f64, index))
+                .collect::<Vec<_>>();
+            stochastic_universal_sampling(&ranked, n, unique)
+        }
+        Selection::Truncation { fraction } => {
+            let mut sorted = weights.to_vec();
+            sorted.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let mut size = (fraction * sorted.len() as f64).ceil() as usize;
+            if unique {
+                size = size.max(n);
+            }
+            sorted.truncate(size.max(1));
+            if unique {
+                sorted.shuffle(&mut rng);
+                sorted.into_iter().take(n).map(|(_, index)| index).collect()
+            } else {
+                (0..n).map(|_| sorted.choose(&mut rng).unwrap().1).collect()
+            }
+        }
+    }
+}
+
+pub(crate) fn dominates(a: &[f64], b: &[f64], directions: &[Direction]) -> bool {
+    let mut better = false;
+    for ((a, b), direction) in a.iter().zip(b).zip(directions) {
+        match direction.compare(*a, *b) {
+            cmp::Ordering::Less => return false,
+            cmp::Ordering::Greater => better = true,
+            cmp::Ordering::Equal => {}
+        }
+    }
+    better
+}
+
+// Fast non-dominated sorting (NSGA-II). Returns the front index of each point,
+// 0 being the Pareto front; points without objective values get usize::MAX.
+pub(crate) fn non_dominated_sort(
+    points: &[Option<Vec<f64>>],
+    directions: &[Direction],
+) -> Vec<usize> {
+    let n = points.len();
+    let mut ranks = vec![usize::MAX; n];
+    let mut dominated = vec![Vec::new(); n];
+    let mut counts = vec![0usize; n];
+    for i in 0..n {
+        let Some(a) = &points[i] else {
+            continue;
+        };
+        for (j, b) in points.iter().enumerate() {
+            let Some(b) = b else {
+                continue;
+            };
+            if dominates(a, b, directions) {
+                dominated[i].push(j);
+            } else if dominates(b, a, directions) {
+                counts[i] += 1;
+            }
+        }
+    }
+
+    let mut front = (0..n)
+        .filter(|&i| points[i].is_some() && counts[i] == 0)
+        .collect::<Vec<_>>();
+    let mut rank = 0;
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &i in &front {
+            ranks[i] = rank;
+            for &j in &dominated[i] {
+                counts[j] -= 1;
+                if counts[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        front = next;
+        rank += 1;
+    }
+    ranks
+}
+
+pub(crate) fn crowding_distance(points: &[Option<Vec<f64>>], ranks: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; points.len()];
+    let mut fronts = BTreeMap::<usize, Vec<usize>>::new();
+    for (index, &rank) in ranks.iter().enumerate() {
+        if rank != usize::MAX {
+            fronts.entry(rank).or_default().push(index);
+        }
+    }
+
+    for mut front in fronts.into_values() {
+        let dimension = points[front[0]].as_ref().unwrap().len();
+        for m in 0..dimension {
+            let value = |i: usize| points[i].as_ref().unwrap()[m];
+            front.sort_by(|&a, &b| value(a).total_cmp(&value(b)));
+            let (first, last) = (front[0], front[front.len() - 1]);
+            distances[first] = f64::INFINITY;
+            distances[last] = f64::INFINITY;
+            let range = value(last) - value(first);
+            if range <= 0.0 {
+                continue;
+            }
+            for k in 1..front.len() - 1 {
+                distances[front[k]] += (value(front[k + 1]) - value(front[k - 1])) / range;
+            }
+        }
+    }
+    distances
+}
+
+// Crowded-comparison order: lower front first, then less crowded first.
+pub(crate) fn crowded_compare(
+    ranks: &[usize],
+    distances: &[f64],
+    a: usize,
+    b: usize,
+) -> cmp::Ordering {
+    ranks[b]
+        .cmp(&ranks[a])
+        .then(distances[a].total_cmp(&distances[b]))
+}
+
+pub(crate) fn crowded_tournament(ranks: &[usize], distances: &[f64], n: usize) -> Vec<usize> {
+    let candidates = (0..ranks.len())
+        .filter(|&i| ranks[i] != usize::MAX)
+        .collect::<Vec<_>>();
+    assert!(!candidates.is_empty());
+    (0..n)
+        .map(|_| {
+            let a = candidates[rand::random_range(0..candidates.len())];
+            let b = candidates[rand::random_range(0..candidates.len())];
+            if crowded_compare(ranks, distances, a, b).is_ge() {
+                a
+            } else {
+                b
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameter::Value;
+
+    #[test]
+    fn test_point_crossover() {
+        let profile = serde_json::from_str::<Profile>(
+            r#"{"a": "Switch", "b": "Switch", "c": "Switch", "d": "Switch"}"#,
+        )
+        .unwrap();
+        let parent = |value| {
+            Individual::new(
+                profile
+                    .0
+                    .keys()
+                    .map(|name| (name.clone(), Value::Switch(value)))
+                    .collect(),
+            )
+        };
+        let options = serde_json::from_str::<Crossover>(
+            r#"{"point": {"points": 1, "probability": {"value": 1.0}}}"#,
+        )
+        .unwrap();
+        for _ in 0..20 {
+            let child = crossover(&profile, &options, &parent(true), &parent(false));
+            let values = child.parameters.values().collect::<Vec<_>>();
+            // exactly one switch from the first parent to the second
+            let changes = values.windows(2).filter(|pair| pair[0] != pair[1]).count();
+            assert_eq!(changes, 1);
+            assert!(*values[0] == Value::Switch(true));
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let weights = (0..10).map(|i| (i as f64, i)).collect::<Vec<_>>();
+
+        let mut selected = select(&Selection::Truncation { fraction: 0.3 }, &weights, 3, true);
+        selected.sort();
+        assert_eq!(selected, vec![7, 8, 9]);
+
+        let selected = select(&Selection::Tournament { size: 10 }, &weights, 2, false);
+        assert_eq!(selected, vec![9, 9]);
+
+        let mut selected = select(&Selection::Rank, &weights, 10, true);
+        selected.sort();
+        assert_eq!(selected, (0..10).collect::<Vec<_>>());
+
+        // parents of zero weight, such as invalid individuals, are never picked
+        let weights = [(0.0, 0), (1.0, 1)];
+        for selection in [
+            Selection::Tournament { size: 2 },
+            Selection::Rank,
+            Selection::Truncation { fraction: 1.0 },
+        ] {
+            assert_eq!(select(&selection, &weights, 20, false), vec![1; 20]);
+        }
+    }
+
+    #[test]
+    fn test_niche_counts() {
+        let profile = serde_json::from_str::<Profile>(
+            r#"{"x": {"Integer": {"space": {"Sequence": [0, 10]}}}, "s": "Switch"}"#,
+        )
+        .unwrap();
+        let individual = |x, s| {
+            Individual::new(
+                [
+                    ("x".into(), Value::Integer(x)),
+                    ("s".into(), Value::Switch(s)),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        };
+        let population = [
+            individual(0, false),
+            individual(2, false),
+            individual(10, true),
+        ];
+        let distance = profile.distance(&population[0].parameters, &population[2].parameters);
+        assert!((distance - 1.0).abs() < 1e-9);
+        let expected = (0.1 + 1.0 + 0.9) / 3.0;
+        assert!((diversity(&profile, &population) - expected).abs() < 1e-9);
+
+        let sharing = Sharing {
+            radius: 0.5,
+            alpha: 1.0,
+        };
+        let niches = niche_counts(&profile, &sharing, &population);
+        assert_eq!(niches.len(), 3);
+        for (niche, expected) in niches.iter().zip([1.8, 1.8, 1.0]) {
+            assert!((niche - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_predict() {
+        let profile = serde_json::from_str::<Profile>(
+            r#"{"x": {"Integer": {"space": {"Sequence": [0, 10]}}}, "s": "Switch"}"#,
+        )
+        .unwrap();
+        let parameters = |x, s| {
+            [
+                ("x".into(), Value::Integer(x)),
+                ("s".into(), Value::Switch(s)),
+            ]
+            .into_iter()
+            .collect::<Combination>()
+        };
+        let (a, b, c) = (
+            parameters(0, false),
+            parameters(2, false),
+            parameters(10, true),
+        );
+        let samples = [(&a, 1.0), (&c, 4.0)];
+        assert_eq!(predict(&profile, &samples, 2, &a), Some(1.0));
+        // 0.1 and 0.9 away, weighted 9:1
+        let estimate = predict(&profile, &samples, 2, &b).unwrap();
+        assert!((estimate - 1.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_operator_probabilities() {
+        let uniform = Operator::probabilities(&[], 0.05);
+        assert_eq!(uniform, vec![0.25; 4]);
+
+        let probabilities = Operator::probabilities(&[1.0, 0.0, 0.0, 1.0], 0.1);
+        assert_eq!(probabilities, vec![0.4, 0.1, 0.1, 0.4]);
+        assert_eq!(Operator::draw(&[0.0, 0.0, 1.0, 0.0]) as usize, 2);
+    }
+
+    #[test]
+    fn test_non_dominated_sort() {
+        let points = vec![
+            Some(vec![1.0, 4.0]),
+            Some(vec![2.0, 2.0]),
+            Some(vec![4.0, 1.0]),
+            Some(vec![3.0, 3.0]),
+            None,
+            Some(vec![4.0, 4.0]),
+        ];
+        let directions = [Direction::Minimize, Direction::Minimize];
+        let ranks = non_dominated_sort(&points, &directions);
+        assert_eq!(ranks, vec![0, 0, 0, 1, usize::MAX, 2]);
+
+        let directions = [Direction::Maximize, Direction::Minimize];
+        let ranks = non_dominated_sort(&points, &directions);
+        assert_eq!(ranks, vec![2, 1, 0, 1, usize::MAX, 1]);
+    }
+
+    #[test]
+    fn test_crowding_distance() {
+        let points = vec![
+            Some(vec![1.0, 4.0]),
+            Some(vec![2.0, 2.0]),
+            Some(vec![4.0, 1.0]),
+        ];
+        let distances = crowding_distance(&points, &[0, 0, 0]);
+        assert!(distances[0].is_infinite());
+        assert!(distances[2].is_infinite());
+        assert!((distances[1] - 2.0).abs() < 1e-9);
+    }
+}
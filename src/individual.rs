@@ -9,10 +9,11 @@ use std::{
     collections::BTreeMap,
     fmt::Display,
     hash::{self, Hash},
+    path::PathBuf,
     sync::Arc,
 };
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub(crate) enum Fitness {
     Valid(f64),
     Invalid,
@@ -61,6 +62,40 @@ impl Fitness {
     }
 }
 
+#[derive(Clone)]
+pub(crate) enum Failure {
+    Signal(i32),
+    Exit(i32),
+    Timeout,
+    Compilation(PathBuf),
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Signal(libc::SIGSEGV) => write!(f, "SIGSEGV"),
+            Failure::Signal(libc::SIGBUS) => write!(f, "SIGBUS"),
+            Failure::Signal(libc::SIGFPE) => write!(f, "SIGFPE"),
+            Failure::Signal(libc::SIGILL) => write!(f, "SIGILL"),
+            Failure::Signal(libc::SIGABRT) => write!(f, "SIGABRT"),
+            Failure::Signal(libc::SIGKILL) => write!(f, "SIGKILL"),
+            Failure::Signal(signal) => write!(f, "signal {}", signal),
+            Failure::Exit(code) => write!(f, "exit code {}", code),
+            Failure::Timeout => write!(f, "timeout"),
+            Failure::Compilation(log) => write!(f, "compilation failed, see {}", log.display()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Sample {
+    pub(crate) fitness: Fitness,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) objectives: Vec<Fitness>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) metrics: BTreeMap<Arc<str>, f64>,
+}
+
 pub(crate) trait Representative<T> {
     fn representative(&self, criterion: Criterion) -> T;
 }
@@ -109,6 +144,14 @@ pub(crate) struct Individual {
 
     // for evaluation
     pub(crate) fitness: Fitness,
+    pub(crate) failure: Option<Failure>,
+    pub(crate) objectives: Vec<Fitness>,
+    pub(crate) metrics: BTreeMap<Arc<str>, Fitness>,
+    pub(crate) samples: Vec<Sample>,
+    // number of samples taken by the latest evaluation
+    pub(crate) measured: usize,
+    // fraction of the full problem the runner is asked to evaluate
+    pub(crate) fidelity: f64,
 }
 
 impl Hash for Individual {
@@ -149,19 +192,90 @@ impl<'de> Deserialize<'de> for Individual {
 }
 
 impl Individual {
+    fn identify(parameters: &Combination) -> Arc<str> {
+        Sha256::digest(serde_json::to_vec(parameters).unwrap())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+            .intern()
+    }
+
     pub(crate) fn new(parameters: BTreeMap<Arc<str>, Value>) -> Self {
         Individual {
-            id: Sha256::digest(serde_json::to_vec(&parameters).unwrap())
-                .iter()
-                .map(|b| format!("{:02x}", b))
-                .collect::<String>()
-                .intern(),
+            id: Self::identify(&parameters),
             parameters,
             arguments: Vec::new(),
             fitness: Fitness::Unknown,
+            failure: None,
+            objectives: Vec::new(),
+            metrics: BTreeMap::new(),
+            samples: Vec::new(),
+            measured: 0,
+            fidelity: 1.0,
         }
     }
 
+    // must be called whenever the parameters are modified in place
+    pub(crate) fn rehash(&mut self) {
+        self.id = Self::identify(&self.parameters);
+    }
+
+    pub(crate) fn status(&self) -> String {
+        let mut status = self.outcome();
+        if !self.metrics.is_empty() {
+            let metrics = self
+                .metrics
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>();
+            status = format!("{} {{{}}}", status, metrics.join(", "));
+        }
+        if self.fidelity != 1.0 {
+            status = format!("{} fidelity={}", status, self.fidelity);
+        }
+        status
+    }
+
+    fn outcome(&self) -> String {
+        if let Some(failure) = &self.failure {
+            format!("{} ({})", self.fitness, failure)
+        } else if self.fitness.is_valid() && !self.objectives.is_empty() {
+            let objectives = self
+                .objectives
+                .iter()
+                .map(|objective| objective.to_string())
+                .collect::<Vec<_>>();
+            format!("{} [{}]", self.fitness, objectives.join(", "))
+        } else {
+            self.fitness.to_string()
+        }
+    }
+
+    // objective values of a valid individual in multi-objective mode
+    pub(crate) fn point(&self) -> Option<Vec<f64>> {
+        if !self.fitness.is_valid() {
+            return None;
+        }
+        self.objectives
+            .iter()
+            .map(|objective| match objective {
+                Fitness::Valid(x) => Some(*x),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // representative metric values, omitting metrics without a valid sample
+    pub(crate) fn metric_values(&self) -> BTreeMap<Arc<str>, f64> {
+        self.metrics
+            .iter()
+            .filter_map(|(name, metric)| match metric {
+                Fitness::Valid(x) => Some((name.clone(), *x)),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub(crate) fn random(profile: &Profile) -> Self {
         let mut individual = Self::new(
             profile
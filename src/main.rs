@@ -1,32 +1,50 @@
+mod cache;
 mod compile;
 mod configuration;
 mod criterion;
+mod database;
 mod direction;
+mod expression;
 mod ffi;
 mod genetic;
 mod individual;
 mod output;
 mod parameter;
+mod sampler;
 mod state;
+mod strategy;
 mod utils;
+mod worker;
 
 use crate::{
-    configuration::{Configuration, StopAction},
-    direction::Direction,
+    cache::Cache,
+    configuration::{Adaptive, Configuration, Isolation, Refinement, StopAction},
+    database::{Database, Policy},
     ffi::{context::Context, helper::Helper, hook::Hook, runner::Runner, workspace::Workspace},
-    individual::{Fitness, Individual, Representative},
+    individual::{Failure, Fitness, Individual, Representative, Sample},
     parameter::IntoJson,
-    utils::{manually_move::ManuallyMove, union::Union},
+    strategy::Strategy,
+    utils::{digest::digest, manually_move::ManuallyMove, union::Union},
+    worker::Outcome,
 };
 use anyhow::anyhow;
 use argh::{FromArgValue, FromArgs};
 use fxhash::FxHashSet;
 use libc::{SIGQUIT, SIGSEGV};
 use libloading::Library;
-use rand::seq::SliceRandom;
-use rayon::iter::{IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 use signal_hook_registry::{register, register_unchecked, unregister};
-use std::{fs, hint, io, path, process, time::SystemTime};
+use std::{
+    collections::BTreeSet,
+    fs, io, path, process,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
 use tempdir::TempDir;
 
 struct CoreIds(Vec<usize>);
@@ -67,6 +85,20 @@ impl FromArgValue for CoreIds {
     }
 }
 
+// Splits the cores into `n` contiguous slots whose sizes differ by at most one.
+fn split_cores(cores: &[usize], n: usize) -> Vec<Vec<usize>> {
+    let size = cores.len() / n;
+    let remainder = cores.len() % n;
+    let mut slots = Vec::with_capacity(n);
+    let mut start = 0;
+    for i in 0..n {
+        let end = start + size + if i < remainder { 1 } else { 0 };
+        slots.push(cores[start..end].to_vec());
+        start = end;
+    }
+    slots
+}
+
 impl AsRef<[usize]> for CoreIds {
     fn as_ref(&self) -> &[usize] {
         &self.0
@@ -95,6 +127,11 @@ struct Options {
     /// CPU cores to use
     cores: CoreIds,
 
+    #[argh(option, default = "1")]
+    /// number of individuals evaluated concurrently, each pinned to its own
+    /// share of the cores (default: 1)
+    slots: usize,
+
     #[argh(option, short = 'r', default = "15")]
     /// number of repetitions for each individual (default: 15)
     repeat: usize,
@@ -115,6 +152,20 @@ struct Options {
     /// path to working directory (default: auto-generated)
     working_dir: Option<String>,
 
+    #[argh(option)]
+    /// path to build cache shared between runs (default: inside working
+    /// directory)
+    cache: Option<String>,
+
+    #[argh(option)]
+    /// path to database of measurements kept across runs (default: none)
+    database: Option<String>,
+
+    #[argh(option, default = "Policy::Reuse")]
+    /// how to treat individuals already in the database: reuse, remeasure
+    /// or blend (default: reuse)
+    database_policy: Policy,
+
     #[argh(option)]
     /// path to log file for generation summary (default: stdout)
     log_summary: Option<String>,
@@ -142,7 +193,7 @@ impl WorkingDir {
     }
 }
 
-impl<'a, P: AsRef<path::Path>> TryFrom<Option<P>> for WorkingDir {
+impl<P: AsRef<path::Path>> TryFrom<Option<P>> for WorkingDir {
     type Error = io::Error;
 
     fn try_from(value: Option<P>) -> Result<Self, Self::Error> {
@@ -161,11 +212,16 @@ impl<'a, P: AsRef<path::Path>> TryFrom<Option<P>> for WorkingDir {
 
 struct Autotuner<'a> {
     sources: &'a [String],
+    helper_sources: &'a [String],
     configuration: Configuration,
-    cores: &'a [usize],
+    slots: Vec<Vec<usize>>,
     working_dir: WorkingDir,
+    cache: Cache,
+    database: Option<Database>,
     helper: Library,
     hook: Library,
+    // post hooks share the workspace, so slots run them one at a time
+    post: Mutex<()>,
     workspace: Workspace<'a>,
 }
 
@@ -182,19 +238,40 @@ impl<'a> Drop for Autotuner<'a> {
 }
 
 impl<'a> Autotuner<'a> {
+    #[allow(clippy::too_many_arguments)]
     fn new<P: AsRef<path::Path>>(
         sources: &'a [String],
         helper: &'a [String],
         hook: &'a [String],
         configuration: Configuration,
         cores: &'a [usize],
+        slots: usize,
         working_dir: Option<P>,
+        cache: Option<P>,
+        database: Option<P>,
+        policy: Policy,
     ) -> anyhow::Result<Self> {
-        if configuration.hyperparameters.initial_population <= 1 {
-            return Err(anyhow!("Initial population size must be greater than 1"));
+        if configuration.isolation == Isolation::None
+            && (configuration.timeout.repetition.is_some()
+                || configuration.timeout.individual.is_some())
+        {
+            return Err(anyhow!("Timeout requires process isolation"));
         }
-        if configuration.hyperparameters.generate.value == 0 {
-            return Err(anyhow!("Number of each generation must be greater than 0"));
+
+        if let Some(adaptive) = &configuration.adaptive
+            && (adaptive.width <= 0.0 || adaptive.confidence <= 0.0 || adaptive.confidence >= 1.0)
+        {
+            return Err(anyhow!(
+                "Adaptive repetition requires a positive width and a confidence between 0 and 1"
+            ));
+        }
+
+        if let Some(objective) = configuration.objectives.first()
+            && objective.direction != configuration.direction
+        {
+            return Err(anyhow!(
+                "Direction must match the direction of the first objective"
+            ));
         }
 
         if cores.is_empty() {
@@ -203,12 +280,27 @@ impl<'a> Autotuner<'a> {
             ));
         }
 
+        if slots == 0 || slots > cores.len() {
+            return Err(anyhow!(
+                "Number of slots must be between 1 and the number of CPU cores"
+            ));
+        }
+        if slots > 1 && configuration.isolation == Isolation::None {
+            return Err(anyhow!("Concurrent evaluation requires process isolation"));
+        }
+
         let working_dir = WorkingDir::try_from(working_dir)?;
         let individuals_dir = working_dir.path().join("individuals");
         if !individuals_dir.exists() {
             fs::create_dir(&individuals_dir)?;
         }
+        let cache = if let Some(cache) = cache {
+            Cache::new(cache)?
+        } else {
+            Cache::new(working_dir.path().join("cache"))?
+        };
 
+        let helper_sources = helper;
         let path = working_dir.path().join("libhelper.so");
         compile::compile(
             &configuration.compiler,
@@ -234,326 +326,222 @@ impl<'a> Autotuner<'a> {
             initializer.call(&mut workspace);
         }
 
-        Ok(Autotuner {
+        let mut autotuner = Autotuner {
             sources,
+            helper_sources,
             configuration,
             working_dir,
+            cache,
+            database: None,
             helper,
             hook,
+            post: Mutex::new(()),
             workspace,
-            cores,
-        })
+            slots: split_cores(cores, slots),
+        };
+        // the fingerprint depends on the fully built tuner
+        if let Some(database) = database {
+            autotuner.database = Some(Database::open(database, policy, autotuner.environment())?);
+        }
+        Ok(autotuner)
     }
 
     fn run(
         &'a self,
+        strategy: &mut dyn Strategy,
         repetition: usize,
         candidates: usize,
-        checkpoint: Option<state::State>,
         log_summary: &mut dyn io::Write,
         log_individual: &mut dyn io::Write,
         log_invalid: bool,
-    ) -> Union<serde_json::Value, state::State> {
-        let is_signaled = ManuallyMove::new(false);
+    ) -> Union<serde_json::Value, serde_json::Value> {
+        let is_signaled = ManuallyMove::new(AtomicBool::new(false));
         let sigquit_handler = unsafe {
             let is_signaled = is_signaled.clone();
             register(SIGQUIT, move || {
-                let mut is_signaled = is_signaled.mov();
-                *is_signaled = true;
+                let is_signaled = is_signaled.mov();
+                is_signaled.store(true, Ordering::Relaxed);
             })
         };
 
-        let mut output = output::Output::new(self.configuration.direction, candidates);
-        let mut state = if let Some(state) = checkpoint {
-            state
-        } else {
-            state::State::new(
-                &self.configuration.hyperparameters,
-                &self.configuration.profile,
-            )
-        };
-
-        let mut rng = rand::rng();
-        // Rust compiler somehow optimizes this function call or later is_gt() call in wrong way
-        // so wrap this call with black_box to prevent optimization
-        let mut best_overall = hint::black_box(self.configuration.direction.worst());
-        loop {
-            // remove duplicates
-            let mut seen = FxHashSet::default();
-            for individual in &mut state.population {
-                if seen.insert(individual.id.clone()) {
-                    continue;
-                }
-
-                let mut replacement = Individual::random(&self.configuration.profile);
-                while !seen.insert(replacement.id.clone()) {
-                    replacement = Individual::random(&self.configuration.profile);
+        let mut report = |individual: &Individual| {
+            if individual.fitness.is_valid()
+                || (log_invalid && individual.fitness == Fitness::Invalid)
+            {
+                let mut status = individual.status();
+                if self.configuration.adaptive.is_some() {
+                    status = format!("{} n={}", status, individual.measured);
                 }
-                *individual = replacement;
+                write!(
+                    log_individual,
+                    "{}\n{}\n",
+                    status,
+                    self.configuration.profile.individual_to_string(individual)
+                )
+                .unwrap();
             }
-            drop(seen);
-
-            state.population.par_iter_mut().for_each(|individual| {
-                self.compile(individual);
-            });
-
-            // evaluate individuals
-            let len = state.population.len();
-            let mut index = 0;
-            while index < len {
-                guard!(SIGQUIT, {
-                    let individual = &mut state.population[index];
-                    if let Fitness::Unknown = individual.fitness {
-                        self.evaluate(individual, repetition);
-                        if individual.fitness.is_valid() || log_invalid {
-                            write!(
-                                log_individual,
-                                "{}\n{}\n",
-                                individual.fitness,
-                                self.configuration.profile.individual_to_string(individual)
-                            )
-                            .unwrap();
-                        }
-                    }
-                });
-
-                if *is_signaled {
-                    break;
-                }
+        };
 
-                output.ranking.push(&state.population[index]);
-                index += 1;
-            }
+        let mut output = output::Output::new(
+            self.configuration.direction,
+            &self.configuration.objectives,
+            candidates,
+        );
 
-            if *is_signaled {
+        // ids evaluated at full fidelity, which the refinement does not repeat
+        let mut evaluated = FxHashSet::default();
+        loop {
+            let mut batch = strategy.ask();
+            if batch.is_empty() {
                 break;
             }
 
-            let mut flattened = state
-                .population
-                .iter()
-                .enumerate()
-                .map(|(index, individual)| {
-                    (
-                        individual.fitness.into_f64(self.configuration.criterion),
-                        index,
-                    )
+            let recalled = batch
+                .par_iter_mut()
+                .map(|individual| {
+                    let recalled = self.recall(individual);
+                    self.compile(individual);
+                    recalled
                 })
                 .collect::<Vec<_>>();
 
-            // record generation summary
-            let iter = flattened.iter().map(|(x, _)| *x).filter(|x| x.is_finite());
-            let boundaries = self.configuration.direction.boundaries(iter);
-            let summary =
-                genetic::GenerationSummary::new(output.ranking.best().unwrap(), boundaries);
-            writeln!(
-                log_summary,
-                "=== Generation #{} Summary ===",
-                state.generation
-            )
-            .unwrap();
-            summary
-                .print(log_summary, &self.configuration.unit)
-                .unwrap();
-            output.history.push(summary);
-
-            let (best, worst) = boundaries;
-            if self
-                .configuration
-                .direction
-                .compare(best, best_overall)
-                .is_gt()
-            {
-                state.count = 0;
-                best_overall = best;
-            } else {
-                state.count += 1;
-            }
+            self.evaluate_all(&mut batch, &recalled, repetition, &is_signaled, &mut report);
 
-            // termination check
-            state.generation += 1;
-            if let Some(limit) = state.hyperparameters.terminate.limit {
-                if state.generation > limit {
-                    break;
-                }
+            if is_signaled.load(Ordering::Relaxed) {
+                break;
             }
 
-            if let Some(goal) = state.hyperparameters.terminate.goal {
-                if self
-                    .configuration
-                    .direction
-                    .compare(best_overall, goal)
-                    .is_ge()
-                {
-                    state.hyperparameters.terminate.goal = None;
-                }
-            } else if let Some(endure) = state.hyperparameters.terminate.endure {
-                write!(log_summary, "{}/{}\n", state.count, endure).unwrap();
-                if state.count == endure {
-                    break;
+            for individual in &batch {
+                output.push(individual);
+                if individual.fidelity == 1.0 {
+                    evaluated.insert(individual.id.clone());
                 }
             }
 
-            writeln!(log_summary).unwrap();
-
-            // select individuals to remove
-            // deletion weight = distance from best (worse individuals get larger
-            // weight, so they are more likely to be deleted); invalid individuals
-            // get the maximum weight to be deleted first.
-            let max_delete_weight = (best - worst).abs();
-            let mut inverted = flattened.clone();
-            for pair in &mut inverted {
-                if pair.0.is_infinite() {
-                    pair.0 = max_delete_weight;
-                    continue;
-                }
+            let mut progress =
+                strategy::Progress::new(&mut output, log_summary, &self.configuration.unit);
+            strategy.tell(batch, &mut progress);
+        }
 
-                pair.0 = match self.configuration.direction {
-                    Direction::Minimize => pair.0 - best,
-                    Direction::Maximize => best - pair.0,
-                };
-            }
-            match self.configuration.direction {
-                Direction::Minimize => inverted.sort_by(|a, b| b.0.total_cmp(&a.0)),
-                Direction::Maximize => inverted.sort_by(|a, b| a.0.total_cmp(&b.0)),
-            }
-            inverted.truncate(inverted.len() - state.hyperparameters.remain);
-            inverted.shuffle(&mut rng);
-            let mut holes = genetic::stochastic_universal_sampling(
-                &inverted,
-                state.hyperparameters.delete.value,
-                true,
+        if let Some(refinement) = &self.configuration.refinement
+            && !is_signaled.load(Ordering::Relaxed)
+        {
+            self.refine(
+                refinement,
+                &mut output,
+                evaluated,
+                repetition,
+                &is_signaled,
+                &mut report,
+                log_summary,
             );
-            drop(inverted);
-
-            // parent selection weight = distance from worst (better individuals get
-            // larger weight, so they are more likely to be selected); invalid
-            // individuals get zero weight so they are never selected as parents.
-            for result in &mut flattened {
-                if result.0.is_infinite() {
-                    result.0 = 0.0;
-                    continue;
-                }
-
-                result.0 = match self.configuration.direction {
-                    Direction::Minimize => worst - result.0,
-                    Direction::Maximize => result.0 - worst,
-                };
-            }
-            flattened.shuffle(&mut rng);
-
-            // generate & evaluate children
-            let mut children = Vec::with_capacity(state.hyperparameters.generate.value);
-            while children.len() < state.hyperparameters.generate.value {
-                let num_children = children.len();
-                let num_current = state.hyperparameters.generate.value - num_children;
-                let mut current = (0..num_current)
-                    .into_par_iter()
-                    .map(|_| {
-                        let result = genetic::stochastic_universal_sampling(&flattened, 2, false);
-                        let mut child = genetic::crossover(
-                            &self.configuration.profile,
-                            &state.population[result[0]],
-                            &state.population[result[1]],
-                        );
-                        genetic::mutate(
-                            &self.configuration.profile,
-                            &state.hyperparameters.mutate,
-                            &mut child,
-                        );
-                        self.compile(&mut child);
-                        child
-                    })
-                    .collect::<Vec<_>>();
-
-                let mut index = 0;
-                while index < num_current {
-                    guard!(SIGQUIT, {
-                        let child = &mut current[index];
-                        if let Fitness::Unknown = child.fitness {
-                            self.evaluate(child, repetition);
-                            if child.fitness.is_valid() || log_invalid {
-                                write!(
-                                    log_individual,
-                                    "{}\n{}\n",
-                                    child.fitness,
-                                    self.configuration.profile.individual_to_string(child)
-                                )
-                                .unwrap();
-                            }
-                        }
-                    });
+        }
 
-                    if *is_signaled {
-                        break;
-                    }
+        let output = if is_signaled.load(Ordering::Relaxed)
+            && self.configuration.stop_action == StopAction::SaveState
+        {
+            second!(strategy.checkpoint())
+        } else {
+            first!(output.into_json(&self.configuration.profile))
+        };
 
-                    output.ranking.push(&current[index]);
-                    index += 1;
-                }
+        ManuallyMove::drop(is_signaled);
 
-                if *is_signaled {
-                    break;
-                }
+        if let Ok(sigquit_handler) = sigquit_handler {
+            unregister(sigquit_handler);
+        }
 
-                for child in current {
-                    if child.fitness.is_valid() {
-                        children.push(child);
-                    }
-                }
-            }
+        output
+    }
 
-            if *is_signaled {
+    // Hill-climbs from the best individuals of the ranking, moving each to its
+    // best neighbour until none improves.
+    #[allow(clippy::too_many_arguments)]
+    fn refine(
+        &self,
+        refinement: &Refinement,
+        output: &mut output::Output,
+        // individuals already evaluated at full fidelity
+        mut visited: FxHashSet<Arc<str>>,
+        repetition: usize,
+        is_signaled: &AtomicBool,
+        report: &mut dyn FnMut(&Individual),
+        log_summary: &mut dyn io::Write,
+    ) {
+        let direction = self.configuration.direction;
+        let criterion = self.configuration.criterion;
+        let mut climbers = output.ranking.top(refinement.top);
+        visited.extend(climbers.iter().map(|climber| climber.id.clone()));
+        let mut round = 0;
+        while !climbers.is_empty() {
+            round += 1;
+            let neighbourhoods = climbers
+                .iter()
+                .map(|climber| {
+                    self.configuration
+                        .profile
+                        .neighbours(climber, refinement.step)
+                        .into_iter()
+                        .filter(|neighbour| visited.insert(neighbour.id.clone()))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let sizes = neighbourhoods.iter().map(Vec::len).collect::<Vec<_>>();
+            let mut batch = neighbourhoods.into_iter().flatten().collect::<Vec<_>>();
+            if batch.is_empty() {
                 break;
             }
 
-            // replace individuals with children
-            let min = state
-                .hyperparameters
-                .generate
-                .value
-                .min(state.hyperparameters.delete.value);
-            let generated = children.split_off(min);
-            let mut deleted = holes.split_off(min);
-            assert!(generated.is_empty() || deleted.is_empty());
-            for (index, child) in children.into_iter().enumerate() {
-                state.population[holes[index]] = child;
-            }
-            if !generated.is_empty() {
-                for child in generated.into_iter() {
-                    state.population.push(child);
-                }
+            let recalled = batch
+                .par_iter_mut()
+                .map(|individual| {
+                    let recalled = self.recall(individual);
+                    self.compile(individual);
+                    recalled
+                })
+                .collect::<Vec<_>>();
+            self.evaluate_all(&mut batch, &recalled, repetition, is_signaled, report);
+            if is_signaled.load(Ordering::Relaxed) {
+                return;
             }
-            if !deleted.is_empty() {
-                deleted.sort();
-                for index in deleted.into_iter().rev() {
-                    // FIXME: strange behavior
-                    state.population.remove(index);
-                }
+            for individual in &batch {
+                output.ranking.push(individual);
             }
 
-            for _ in 0..state.hyperparameters.infuse.value {
-                state
-                    .population
-                    .push(Individual::random(&self.configuration.profile));
+            let boundaries = direction.boundaries(
+                batch
+                    .iter()
+                    .map(|individual| individual.fitness.into_f64(criterion))
+                    .filter(|x| x.is_finite()),
+            );
+            if boundaries.0.is_finite() {
+                let summary =
+                    genetic::GenerationSummary::new(output.ranking.best().unwrap(), boundaries);
+                let mut progress =
+                    strategy::Progress::new(output, log_summary, &self.configuration.unit);
+                progress.summarize(format!("Refinement #{}", round), summary);
+                writeln!(log_summary).unwrap();
             }
 
-            state.step();
-        }
-
-        let output = if *is_signaled && self.configuration.stop_action == StopAction::SaveState {
-            second!(state.into())
-        } else {
-            first!(output.into_json(&self.configuration.profile))
-        };
-
-        ManuallyMove::drop(is_signaled);
-
-        if let Ok(sigquit_handler) = sigquit_handler {
-            unregister(sigquit_handler);
+            let mut neighbours = batch.into_iter();
+            climbers = climbers
+                .into_iter()
+                .zip(sizes)
+                .filter_map(|(climber, size)| {
+                    let current = climber.fitness.into_f64(criterion);
+                    neighbours
+                        .by_ref()
+                        .take(size)
+                        .filter_map(|neighbour| match neighbour.fitness {
+                            Fitness::Valid(fitness) => Some((fitness, neighbour)),
+                            _ => None,
+                        })
+                        .max_by(|(a, _), (b, _)| direction.compare(*a, *b))
+                        .filter(|(fitness, _)| direction.compare(*fitness, current).is_gt())
+                        .map(|(_, neighbour)| neighbour)
+                })
+                .collect();
         }
-
-        output
     }
 
     #[inline]
@@ -564,7 +552,114 @@ impl<'a> Autotuner<'a> {
             .join(individual.id.as_ref())
     }
 
+    // Fingerprint of everything besides the parameters that affects measurements.
+    fn environment(&self) -> String {
+        digest(
+            database::machine()
+                .into_iter()
+                .chain(self.slots.iter().map(|cores| format!("{:?}", cores)))
+                .chain([
+                    self.configuration.runner.clone(),
+                    self.configuration.compiler.clone(),
+                ])
+                .chain(self.configuration.compiler_arguments.iter().cloned())
+                .chain(self.sources.iter().cloned())
+                .chain(self.helper_sources.iter().cloned())
+                // the fitness and objectives of each sample are stored as computed
+                .chain(
+                    self.configuration
+                        .fitness
+                        .iter()
+                        .map(|fitness| format!("fitness={}", fitness)),
+                )
+                .chain(
+                    self.configuration
+                        .objectives
+                        .iter()
+                        .map(|objective| format!("objective={}", objective.name)),
+                ),
+        )
+    }
+
+    // Reduces the samples of an individual to its fitness and objective values.
+    // With multiple objectives the fitness is the value of the first one.
+    fn summarize(&self, individual: &mut Individual) {
+        let criterion = self.configuration.criterion;
+        individual.objectives = (0..self.configuration.objectives.len())
+            .map(|m| {
+                individual
+                    .samples
+                    .iter()
+                    .map(|sample| {
+                        sample
+                            .objectives
+                            .get(m)
+                            .copied()
+                            .unwrap_or(Fitness::Invalid)
+                    })
+                    .collect::<Vec<_>>()
+                    .representative(criterion)
+            })
+            .collect();
+        let names = individual
+            .samples
+            .iter()
+            .flat_map(|sample| sample.metrics.keys().cloned())
+            .collect::<BTreeSet<_>>();
+        individual.metrics = names
+            .into_iter()
+            .map(|name| {
+                let metric = individual
+                    .samples
+                    .iter()
+                    .map(|sample| {
+                        sample
+                            .metrics
+                            .get(&name)
+                            .map_or(Fitness::Invalid, |x| Fitness::Valid(*x))
+                    })
+                    .collect::<Vec<_>>()
+                    .representative(criterion);
+                (name, metric)
+            })
+            .collect();
+        individual.fitness = if individual.objectives.is_empty() {
+            individual
+                .samples
+                .iter()
+                .map(|sample| sample.fitness)
+                .collect::<Vec<_>>()
+                .representative(criterion)
+        } else if individual.objectives.iter().all(Fitness::is_valid) {
+            individual.objectives[0]
+        } else {
+            Fitness::Invalid
+        };
+    }
+
+    // Returns whether the individual was loaded from the database.
+    fn recall(&self, individual: &mut Individual) -> bool {
+        let database = match &self.database {
+            Some(database) if database.policy() == Policy::Reuse => database,
+            _ => return false,
+        };
+        if individual.fitness != Fitness::Unknown {
+            return false;
+        }
+        if let Some(samples) = database.get(individual) {
+            individual.samples = samples;
+            self.summarize(individual);
+            return true;
+        }
+        false
+    }
+
     fn compile(&self, individual: &mut Individual) {
+        // already evaluated (or invalidated) individuals need no binary
+        if individual.fitness != Fitness::Unknown || individual.failure.is_some() {
+            return;
+        }
+
         let working_directory = self.get_working_directory(individual);
         if !working_directory.exists() {
             let result = fs::create_dir(&working_directory);
@@ -573,11 +668,7 @@ impl<'a> Autotuner<'a> {
             }
         }
 
-        let path = working_directory.join("lib.so");
-        if path.exists() {
-            return;
-        }
-
+        individual.arguments.clear();
         let mut context = Context::new(self, individual);
         for name in &self.configuration.hooks.pre {
             unsafe {
@@ -590,61 +681,195 @@ impl<'a> Autotuner<'a> {
             return;
         }
 
-        compile::compile(
-            &self.configuration.compiler,
-            &path,
+        let arguments = || {
             self.sources
                 .iter()
                 .chain(self.configuration.compiler_arguments.iter())
-                .chain(context.individual.arguments.iter()),
-        )
-        .unwrap();
+                .chain(context.individual.arguments.iter())
+        };
+        let key = Cache::key(&self.configuration.compiler, arguments());
+        let cached = if let Some(cached) = self.cache.get(&key) {
+            cached
+        } else {
+            let temporary = self.cache.temporary(&key);
+            if let Err(error) =
+                compile::compile(&self.configuration.compiler, &temporary, arguments())
+            {
+                let _ = fs::remove_file(&temporary);
+                let log = working_directory.join("compile.log");
+                let _ = fs::write(&log, error.to_string());
+                context.individual.failure = Some(Failure::Compilation(log));
+                return;
+            }
+            self.cache.insert(&key, &temporary).unwrap()
+        };
+
+        cache::install(&cached, &working_directory.join("lib.so")).unwrap();
     }
 
-    fn evaluate(&self, individual: &mut Individual, repetition: usize) {
+    fn evaluate_all(
+        &self,
+        individuals: &mut [Individual],
+        recalled: &[bool],
+        repetition: usize,
+        is_signaled: &AtomicBool,
+        report: &mut dyn FnMut(&Individual),
+    ) {
+        // individuals recalled from the database are logged without being measured
+        for (individual, _) in individuals
+            .iter()
+            .zip(recalled)
+            .filter(|(_, recalled)| **recalled)
+        {
+            report(individual);
+        }
+        let mut pending = individuals
+            .iter_mut()
+            .filter(|individual| individual.fitness == Fitness::Unknown)
+            .collect::<Vec<_>>();
+
+        if self.slots.len() == 1 {
+            // evaluate on the current thread, as runners may rely on thread-local state
+            for individual in pending {
+                if is_signaled.load(Ordering::Relaxed) {
+                    break;
+                }
+                self.evaluate(individual, repetition, &self.slots[0], is_signaled);
+                report(individual);
+            }
+            return;
+        }
+
+        let pending = pending.iter_mut().map(Mutex::new).collect::<Vec<_>>();
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for cores in &self.slots {
+                let sender = sender.clone();
+                let pending = &pending;
+                let next = &next;
+                scope.spawn(move || {
+                    loop {
+                        if is_signaled.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= pending.len() {
+                            break;
+                        }
+                        self.evaluate(
+                            &mut pending[index].lock().unwrap(),
+                            repetition,
+                            cores,
+                            is_signaled,
+                        );
+                        let _ = sender.send(index);
+                    }
+                });
+            }
+            drop(sender);
+
+            for index in receiver {
+                report(&pending[index].lock().unwrap());
+            }
+        });
+    }
+
+    fn evaluate(
+        &self,
+        individual: &mut Individual,
+        repetition: usize,
+        cores: &[usize],
+        is_signaled: &AtomicBool,
+    ) {
+        if individual.failure.is_some() {
+            individual.fitness = Fitness::Invalid;
+            return;
+        }
+
         let working_directory = self.get_working_directory(individual);
         if !working_directory.exists() {
             return;
         }
 
-        let path = working_directory.join("lib.so");
-        let lib = unsafe { Library::new(&path) }.unwrap();
-        let runner = unsafe { lib.get::<Runner>(self.configuration.runner.as_bytes()) }.unwrap();
-
-        let mut context = Context::new(self, individual);
-        let mut fitnesses = Vec::with_capacity(repetition);
-        for _ in 0..repetition {
-            unsafe {
-                let result = register_unchecked(SIGSEGV, |_| {
-                    // can we do better than this?
-                    eprintln!("Segmentation fault occurred during evaluation");
-                    process::exit(1);
+        let mut samples = match self.configuration.isolation {
+            Isolation::None => {
+                let mut samples = Vec::with_capacity(repetition);
+                guard!(SIGQUIT, {
+                    let result = unsafe {
+                        register_unchecked(SIGSEGV, |_| {
+                            // can we do better than this?
+                            eprintln!("Segmentation fault occurred during evaluation");
+                            process::exit(1);
+                        })
+                    };
+                    self.measure(individual, repetition, cores, &mut |sample| {
+                        samples.push(sample)
+                    });
+                    if let Ok(id) = result {
+                        unregister(id);
+                    }
                 });
-                let affinity = if self.cores.is_empty() {
-                    None
-                } else {
-                    let affinity = affinity::get_thread_affinity().unwrap();
-                    affinity::set_thread_affinity(&self.cores).unwrap();
-                    Some(affinity)
+                samples
+            }
+            Isolation::Process => {
+                let limits = worker::Limits {
+                    record: self
+                        .configuration
+                        .timeout
+                        .repetition
+                        .map(Duration::from_secs_f64),
+                    total: self
+                        .configuration
+                        .timeout
+                        .individual
+                        .map(Duration::from_secs_f64),
                 };
-                runner.call(&mut context);
-                if let Some(affinity) = affinity {
-                    affinity::set_thread_affinity(&affinity).unwrap();
-                }
-                if let Ok(id) = result {
-                    unregister(id);
+                match worker::spawn(
+                    |record| self.measure(individual, repetition, cores, record),
+                    &limits,
+                    &|| is_signaled.load(Ordering::Relaxed),
+                )
+                .expect("Failed to spawn evaluation worker")
+                {
+                    Outcome::Exited(samples) => samples,
+                    Outcome::Failed(code) => {
+                        individual.failure = Some(Failure::Exit(code));
+                        Vec::new()
+                    }
+                    Outcome::Signaled(signal) => {
+                        individual.failure = Some(Failure::Signal(signal));
+                        Vec::new()
+                    }
+                    Outcome::Timeout => {
+                        individual.failure = Some(Failure::Timeout);
+                        Vec::new()
+                    }
+                    // leave the individual unevaluated so that it is measured again on resume
+                    Outcome::Interrupted => return,
                 }
-            };
-            if context.individual.fitness.is_nan() {
-                panic!("NaN value encountered");
             }
-            fitnesses.push(context.individual.fitness);
-        }
-
-        drop(lib);
+        };
 
-        context.individual.fitness = fitnesses.representative(self.configuration.criterion);
+        individual.measured = samples.len();
+        if let Some(database) = &self.database
+            && database.policy() == Policy::Blend
+            && let Some(mut previous) = database.get(individual)
+        {
+            previous.append(&mut samples);
+            samples = previous;
+        }
+        individual.samples = samples;
+        self.summarize(individual);
+        if let Some(database) = &self.database {
+            let parameters = self.configuration.profile.individual_to_string(individual);
+            database
+                .insert(individual, parameters)
+                .expect("Failed to write to database");
+        }
 
+        let mut context = Context::new(self, individual);
+        let _post = self.post.lock().unwrap();
         for name in &self.configuration.hooks.post {
             unsafe {
                 let task = self.hook.get::<Hook>(name.as_bytes()).unwrap();
@@ -652,6 +877,108 @@ impl<'a> Autotuner<'a> {
             }
         }
     }
+
+    fn measure(
+        &self,
+        individual: &mut Individual,
+        repetition: usize,
+        cores: &[usize],
+        record: &mut dyn FnMut(Sample),
+    ) {
+        let path = self.get_working_directory(individual).join("lib.so");
+        let lib = unsafe { Library::new(&path) }.unwrap();
+        let runner = unsafe { lib.get::<Runner>(self.configuration.runner.as_bytes()) }.unwrap();
+
+        // `repetition` is the minimum number of samples in adaptive mode
+        let maximum = match &self.configuration.adaptive {
+            Some(adaptive) => adaptive.maximum.max(repetition),
+            None => repetition,
+        };
+        let mut values = Vec::new();
+        let mut context = Context::new(self, individual);
+        for taken in 1..=maximum {
+            let affinity = if cores.is_empty() {
+                None
+            } else {
+                let affinity = affinity::get_thread_affinity().unwrap();
+                affinity::set_thread_affinity(cores).unwrap();
+                Some(affinity)
+            };
+            context.individual.objectives =
+                vec![Fitness::Unknown; self.configuration.objectives.len()];
+            context.individual.metrics.clear();
+            runner.call(&mut context);
+            if let Some(affinity) = affinity {
+                affinity::set_thread_affinity(&affinity).unwrap();
+            }
+            let individual = &mut *context.individual;
+            if individual.fitness.is_nan()
+                || individual.objectives.iter().any(Fitness::is_nan)
+                || individual.metrics.values().any(Fitness::is_nan)
+            {
+                panic!("NaN value encountered");
+            }
+
+            // objectives not set explicitly fall back to the metric of the same name
+            for (objective, declaration) in individual
+                .objectives
+                .iter_mut()
+                .zip(&self.configuration.objectives)
+            {
+                if *objective == Fitness::Unknown
+                    && let Some(metric) = individual.metrics.get(declaration.name.as_str())
+                {
+                    *objective = *metric;
+                }
+            }
+            if let Some(expression) = &self.configuration.fitness {
+                let metrics = &individual.metrics;
+                individual.fitness = match expression.evaluate(&|name| match metrics.get(name) {
+                    Some(Fitness::Valid(x)) => Some(*x),
+                    _ => None,
+                }) {
+                    Some(x) if x.is_finite() => Fitness::Valid(x),
+                    _ => Fitness::Invalid,
+                };
+            }
+
+            record(Sample {
+                fitness: individual.fitness,
+                objectives: individual.objectives.clone(),
+                metrics: individual.metric_values(),
+            });
+
+            if let Some(adaptive) = &self.configuration.adaptive {
+                if let Fitness::Valid(x) =
+                    individual.objectives.first().unwrap_or(&individual.fitness)
+                {
+                    values.push(*x);
+                }
+                if taken >= repetition && self.is_precise(adaptive, &values) {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn is_precise(&self, adaptive: &Adaptive, values: &[f64]) -> bool {
+        // repeating an individual that never yields a valid sample is pointless
+        if values.is_empty() {
+            return true;
+        }
+        let criterion = self.configuration.criterion;
+        let center = values
+            .iter()
+            .map(|x| Fitness::Valid(*x))
+            .collect::<Vec<_>>()
+            .representative(criterion)
+            .into_f64(criterion);
+        match criterion.interval(values, adaptive.confidence) {
+            Some((lower, upper)) if lower == upper => true,
+            Some((lower, upper)) => (upper - lower) <= adaptive.width * center.abs(),
+            None => false,
+        }
+    }
 }
 
 fn main() -> anyhow::Result<()> {
@@ -660,9 +987,10 @@ fn main() -> anyhow::Result<()> {
         fs::read_to_string(&args.configuration).expect("Failed to read configuration file");
     let configuration = serde_json::from_str::<Configuration>(&configuration)
         .expect("Failed to parse configuration file");
-    let state = args.continue_.as_ref().map(|filename| {
+    let checkpoint = args.continue_.as_ref().map(|filename| {
         let content = fs::read_to_string(filename).expect("Failed to read checkpoint file");
-        serde_json::from_str::<state::State>(&content).expect("Failed to parse checkpoint file")
+        serde_json::from_str::<serde_json::Value>(&content)
+            .expect("Failed to parse checkpoint file")
     });
     let mut log_summary = if let Some(ref filename) = args.log_summary {
         Box::new(
@@ -693,13 +1021,18 @@ fn main() -> anyhow::Result<()> {
         &args.hook,
         configuration,
         args.cores.as_ref(),
+        args.slots,
         args.working_dir.as_ref(),
+        args.cache.as_ref(),
+        args.database.as_ref(),
+        args.database_policy,
     )?;
+    let mut strategy = strategy::new(&autotuner.configuration, checkpoint)?;
     match_union!(
         autotuner.run(
+            strategy.as_mut(),
             args.repeat,
             args.candidates,
-            state,
             &mut log_summary,
             &mut log_individual,
             args.log_invalid
@@ -750,4 +1083,15 @@ mod tests {
             assert_eq!(core_ids.as_ref(), expected.as_slice());
         }
     }
+
+    #[test]
+    fn test_split_cores() {
+        let cores = (0..10).collect::<Vec<_>>();
+        assert_eq!(split_cores(&cores, 1), vec![cores.clone()]);
+        assert_eq!(
+            split_cores(&cores, 3),
+            vec![vec![0, 1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]
+        );
+        assert_eq!(split_cores(&cores, 10).len(), 10);
+    }
 }
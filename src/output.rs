@@ -1,6 +1,7 @@
 use crate::{
+    configuration::Objective,
     direction::Direction,
-    genetic::GenerationSummary,
+    genetic::{self, GenerationSummary},
     individual::{Fitness, Individual},
     parameter::{IntoJson, Profile},
 };
@@ -8,17 +9,42 @@ use fxhash::FxHashMap;
 use std::sync::Arc;
 
 pub(crate) struct Output {
+    // at full fidelity
     pub(crate) ranking: Ranking,
     pub(crate) history: Vec<GenerationSummary>,
+    // rankings at lower fidelities, in order of appearance
+    pub(crate) fidelities: Vec<(f64, Ranking)>,
 }
 
 impl Output {
-    pub(crate) fn new(direction: Direction, capacity: usize) -> Self {
+    pub(crate) fn new(direction: Direction, objectives: &[Objective], capacity: usize) -> Self {
         Output {
-            ranking: Ranking::new(direction, capacity),
+            ranking: Ranking::new(direction, objectives, capacity),
             history: Vec::new(),
+            fidelities: Vec::new(),
         }
     }
+
+    // ranks the individual among those evaluated at the same fidelity
+    pub(crate) fn push(&mut self, individual: &Individual) {
+        if individual.fidelity == 1.0 {
+            self.ranking.push(individual);
+            return;
+        }
+        let index = match self
+            .fidelities
+            .iter()
+            .position(|(fidelity, _)| *fidelity == individual.fidelity)
+        {
+            Some(index) => index,
+            None => {
+                let ranking = self.ranking.sibling();
+                self.fidelities.push((individual.fidelity, ranking));
+                self.fidelities.len() - 1
+            }
+        };
+        self.fidelities[index].1.push(individual);
+    }
 }
 
 impl IntoJson for Output {
@@ -26,25 +52,102 @@ impl IntoJson for Output {
         let mut serialized = serde_json::Map::new();
         serialized.insert("ranking".to_string(), self.ranking.into_json(profile));
         serialized.insert("history".to_string(), self.history.into_json(profile));
+        if !self.fidelities.is_empty() {
+            let fidelities = self
+                .fidelities
+                .into_iter()
+                .map(|(fidelity, ranking)| {
+                    serde_json::json!({
+                        "fidelity": fidelity,
+                        "ranking": ranking.into_json(profile),
+                    })
+                })
+                .collect();
+            serialized.insert(
+                "fidelities".to_string(),
+                serde_json::Value::Array(fidelities),
+            );
+        }
         serde_json::Value::Object(serialized)
     }
 }
 
+// Keeps the best `capacity` individuals, or the non-dominated front of at most
+// `capacity` individuals when multiple objectives are declared.
 pub(crate) struct Ranking {
     direction: Direction,
+    objectives: Vec<(String, Direction)>,
     capacity: usize,
     data: FxHashMap<Arc<str>, Individual>,
 }
 
 impl Ranking {
-    pub(crate) fn new(direction: Direction, capacity: usize) -> Self {
+    pub(crate) fn new(direction: Direction, objectives: &[Objective], capacity: usize) -> Self {
         Ranking {
             direction,
+            objectives: objectives
+                .iter()
+                .map(|objective| (objective.name.clone(), objective.direction))
+                .collect(),
             capacity,
             data: FxHashMap::default(),
         }
     }
 
+    // an empty ranking with the same settings
+    fn sibling(&self) -> Self {
+        Ranking {
+            direction: self.direction,
+            objectives: self.objectives.clone(),
+            capacity: self.capacity,
+            data: FxHashMap::default(),
+        }
+    }
+
+    fn directions(&self) -> Vec<Direction> {
+        self.objectives
+            .iter()
+            .map(|(_, direction)| *direction)
+            .collect()
+    }
+
+    fn push_front(&mut self, individual: &Individual) {
+        let point = match individual.point() {
+            Some(point) => point,
+            None => return,
+        };
+        if self.data.contains_key(individual.id.as_ref()) {
+            return;
+        }
+
+        let directions = self.directions();
+        if self.data.values().any(|other| {
+            let other = other.point().unwrap();
+            other == point || genetic::dominates(&other, &point, &directions)
+        }) {
+            return;
+        }
+        self.data
+            .retain(|_, other| !genetic::dominates(&point, &other.point().unwrap(), &directions));
+        self.data.insert(individual.id.clone(), individual.clone());
+
+        // drop the most crowded members of an overfull front
+        while self.data.len() > self.capacity {
+            let keys = self.data.keys().cloned().collect::<Vec<_>>();
+            let points = keys
+                .iter()
+                .map(|key| self.data[key].point())
+                .collect::<Vec<_>>();
+            let distances = genetic::crowding_distance(&points, &vec![0; points.len()]);
+            let (index, _) = distances
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap();
+            self.data.remove(&keys[index]);
+        }
+    }
+
     fn better(&self, lhs: f64, rhs: f64) -> bool {
         self.direction.compare(lhs, rhs).is_gt()
     }
@@ -65,6 +168,11 @@ impl Ranking {
             return;
         }
 
+        if !self.objectives.is_empty() {
+            self.push_front(individual);
+            return;
+        }
+
         let direction = self.direction;
 
         let fitness = match individual.fitness {
@@ -73,10 +181,10 @@ impl Ranking {
         };
 
         if let Some(current) = self.data.get_mut(individual.id.as_ref()) {
-            if let Fitness::Valid(current_fitness) = current.fitness {
-                if direction.compare(fitness, current_fitness).is_gt() {
-                    *current = individual.clone();
-                }
+            if let Fitness::Valid(current_fitness) = current.fitness
+                && direction.compare(fitness, current_fitness).is_gt()
+            {
+                *current = individual.clone();
             }
             return;
         }
@@ -102,6 +210,24 @@ impl Ranking {
         }
     }
 
+    // the best `k` valid individuals, best first
+    pub(crate) fn top(&self, k: usize) -> Vec<Individual> {
+        let mut valid = self
+            .data
+            .values()
+            .filter_map(|individual| match individual.fitness {
+                Fitness::Valid(fitness) => Some((individual, fitness)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        valid.sort_by(|(_, lhs), (_, rhs)| self.direction.compare(*rhs, *lhs));
+        valid
+            .into_iter()
+            .take(k)
+            .map(|(individual, _)| individual.clone())
+            .collect()
+    }
+
     pub(crate) fn best(&self) -> Option<&Individual> {
         self.data
             .values()
@@ -116,19 +242,67 @@ impl Ranking {
 
 impl IntoJson for Ranking {
     fn into_json(self, profile: &Profile) -> serde_json::Value {
+        if !self.objectives.is_empty() {
+            let mut front = self
+                .data
+                .into_values()
+                .filter_map(|individual| {
+                    let point = individual.point()?;
+                    Some((
+                        profile.individual_to_string(&individual),
+                        point,
+                        individual.metric_values(),
+                    ))
+                })
+                .collect::<Vec<_>>();
+            let (_, direction) = self.objectives[0];
+            front.sort_by(|(_, lhs, _), (_, rhs, _)| direction.compare(rhs[0], lhs[0]));
+
+            let front = front
+                .into_iter()
+                .map(|(individual, point, metrics)| {
+                    let objectives = self
+                        .objectives
+                        .iter()
+                        .zip(point)
+                        .map(|((name, _), value)| (name.clone(), serde_json::json!(value)))
+                        .collect::<serde_json::Map<_, _>>();
+                    if metrics.is_empty() {
+                        serde_json::json!((individual, objectives))
+                    } else {
+                        serde_json::json!((individual, objectives, metrics))
+                    }
+                })
+                .collect();
+            return serde_json::Value::Array(front);
+        }
+
         let mut ranking = self
             .data
             .into_values()
             .filter_map(|individual| match individual.fitness {
-                Fitness::Valid(fitness) => {
-                    Some((profile.individual_to_string(&individual), fitness))
-                }
+                Fitness::Valid(fitness) => Some((
+                    profile.individual_to_string(&individual),
+                    fitness,
+                    individual.metric_values(),
+                )),
                 _ => None,
             })
             .collect::<Vec<_>>();
 
-        ranking.sort_by(|&(_, lhs), &(_, rhs)| self.direction.compare(rhs, lhs));
+        ranking.sort_by(|(_, lhs, _), (_, rhs, _)| self.direction.compare(*rhs, *lhs));
 
-        serde_json::to_value(ranking).unwrap()
+        serde_json::Value::Array(
+            ranking
+                .into_iter()
+                .map(|(individual, fitness, metrics)| {
+                    if metrics.is_empty() {
+                        serde_json::json!((individual, fitness))
+                    } else {
+                        serde_json::json!((individual, fitness, metrics))
+                    }
+                })
+                .collect(),
+        )
     }
 }
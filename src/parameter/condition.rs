@@ -2,6 +2,7 @@ use crate::{
     parameter::{Combination, Value},
     utils::interner::Intern,
 };
+use rand::Rng;
 use serde::Deserialize;
 
 #[derive(Deserialize)]
@@ -33,7 +34,8 @@ pub(crate) enum Integer {
 }
 
 impl Integer {
-    pub(crate) fn adjust(&self, name: &str, combination: &mut Combination) {
+    // `rng` breaks the tie when a value lies halfway between two multiples
+    pub(crate) fn adjust(&self, name: &str, combination: &mut Combination, rng: &mut impl Rng) {
         match self {
             Integer::MultipleOf(object) => {
                 let b = object.resolve(combination);
@@ -42,11 +44,9 @@ impl Integer {
                     let remainder = *a % b;
                     if remainder != 0 {
                         let d = if remainder * 2 == b {
-                            rand::random()
-                        } else if remainder * 2 > b {
-                            true
+                            rng.random()
                         } else {
-                            false
+                            remainder * 2 > b
                         };
                         if d {
                             *a += b - remainder;
@@ -3,6 +3,7 @@ mod condition;
 pub(crate) mod space;
 
 use crate::{configuration::Mutation, individual::Individual};
+use rand::Rng;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, sync::Arc};
@@ -12,6 +13,16 @@ pub(crate) trait Space {
     fn adjust(&self, _: &mut Value) {}
     fn crossover(&self, a: Value, b: Value) -> Value;
     fn mutate(&self, mutations: &Mutation, value: &mut Value);
+    // every value in the space, in order
+    fn values(&self) -> Vec<Value>;
+    // the number of values, without enumerating them
+    fn size(&self) -> u64;
+    // values one move away, with `step` the distance for integer sequences
+    fn neighbours(&self, value: Value, step: u32) -> Vec<Value>;
+    // distance between two values, from 0 to 1
+    fn distance(&self, a: Value, b: Value) -> f64;
+    // the value at `u` in [0, 1) when the space is split into equal parts
+    fn quantile(&self, u: f64) -> Value;
 }
 
 #[derive(Deserialize)]
@@ -94,26 +105,77 @@ impl Specification {
 pub(crate) struct Profile(pub(crate) BTreeMap<Arc<str>, Arc<Specification>>);
 
 impl Profile {
-    fn adjust_by(&self, name: &str, combination: &mut Combination) {
-        match self.0[name].as_ref() {
-            Specification::Integer {
-                space,
-                condition: Some(condition),
-            } => {
-                for dependence in condition.get_dependences() {
-                    self.adjust_by(dependence, combination);
-                }
-                condition.adjust(name, combination);
-                space.adjust(combination.get_mut(name).unwrap());
+    fn adjust_by(&self, name: &str, combination: &mut Combination, rng: &mut impl Rng) {
+        if let Specification::Integer {
+            space,
+            condition: Some(condition),
+        } = self.0[name].as_ref()
+        {
+            for dependence in condition.get_dependences() {
+                self.adjust_by(dependence, combination, rng);
             }
-            _ => {}
+            condition.adjust(name, combination, rng);
+            space.adjust(combination.get_mut(name).unwrap());
         }
     }
 
     pub(crate) fn adjust(&self, individual: &mut Individual) {
+        self.adjust_with(individual, &mut rand::rng());
+    }
+
+    pub(crate) fn adjust_with(&self, individual: &mut Individual, rng: &mut impl Rng) {
         for name in self.0.keys() {
-            self.adjust_by(name, &mut individual.parameters);
+            self.adjust_by(name, &mut individual.parameters, rng);
+        }
+        individual.rehash();
+    }
+
+    // Individuals differing from the given one in a single parameter, after
+    // adjustment, without duplicates.
+    pub(crate) fn neighbours(&self, individual: &Individual, step: u32) -> Vec<Individual> {
+        let mut neighbours = Vec::<Individual>::new();
+        for (name, specification) in &self.0 {
+            let value = individual.parameters[name];
+            for neighbour in specification.get_space().neighbours(value, step) {
+                let mut parameters = individual.parameters.clone();
+                parameters.insert(name.clone(), neighbour);
+                let mut neighbour = Individual::new(parameters);
+                self.adjust(&mut neighbour);
+                if neighbour.id != individual.id && !neighbours.contains(&neighbour) {
+                    neighbours.push(neighbour);
+                }
+            }
+        }
+        neighbours
+    }
+
+    // Maps a point of the unit hypercube, one coordinate per parameter in
+    // order, to an adjusted individual.
+    pub(crate) fn unit_to_individual(&self, point: &[f64]) -> Individual {
+        let parameters = self
+            .0
+            .iter()
+            .zip(point)
+            .map(|((name, specification), u)| {
+                (name.clone(), specification.get_space().quantile(*u))
+            })
+            .collect();
+        let mut individual = Individual::new(parameters);
+        self.adjust(&mut individual);
+        individual
+    }
+
+    // mean distance over the parameters, from 0 to 1
+    pub(crate) fn distance(&self, a: &Combination, b: &Combination) -> f64 {
+        if self.0.is_empty() {
+            return 0.0;
         }
+        let sum = self
+            .0
+            .iter()
+            .map(|(name, specification)| specification.get_space().distance(a[name], b[name]))
+            .sum::<f64>();
+        sum / self.0.len() as f64
     }
 
     pub(crate) fn individual_to_string(&self, individual: &Individual) -> String {
@@ -128,12 +190,7 @@ impl Profile {
     pub(crate) fn string_to_individual(&self, s: &str) -> Individual {
         let parameters = s
             .split(", ")
-            .map(|pair| {
-                let mut parts = pair.splitn(2, '=');
-                let name = parts.next().unwrap();
-                let value = parts.next().unwrap();
-                (name, value)
-            })
+            .map(|pair| pair.split_once('=').unwrap())
             .collect::<BTreeMap<&str, &str>>();
         let parameters = self
             .0
@@ -77,6 +77,100 @@ impl Space for Integer {
             }
         }
     }
+
+    fn values(&self) -> Vec<Value> {
+        match self {
+            Integer::Sequence(start, end) => (*start..=*end).map(Value::Integer).collect(),
+            Integer::Candidates(candidates) => (0..candidates.len()).map(Value::Index).collect(),
+        }
+    }
+
+    fn size(&self) -> u64 {
+        match self {
+            Integer::Sequence(start, end) => (*end as u64 + 1).saturating_sub(*start as u64),
+            Integer::Candidates(candidates) => candidates.len() as u64,
+        }
+    }
+
+    fn neighbours(&self, value: Value, step: u32) -> Vec<Value> {
+        match (self, value) {
+            (Integer::Sequence(start, end), Value::Integer(n)) => {
+                [n.checked_sub(step), n.checked_add(step)]
+                    .into_iter()
+                    .flatten()
+                    .filter(|x| (*start..=*end).contains(x))
+                    .map(Value::Integer)
+                    .collect()
+            }
+            (Integer::Candidates(candidates), Value::Index(i)) => [i.checked_sub(1), Some(i + 1)]
+                .into_iter()
+                .flatten()
+                .filter(|j| *j < candidates.len())
+                .map(Value::Index)
+                .collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn distance(&self, a: Value, b: Value) -> f64 {
+        let (a, b, span) = match (self, a, b) {
+            (Integer::Sequence(start, end), Value::Integer(a), Value::Integer(b)) => {
+                (a as usize, b as usize, (end - start) as usize)
+            }
+            (Integer::Candidates(candidates), Value::Index(a), Value::Index(b)) => {
+                (a, b, candidates.len().saturating_sub(1))
+            }
+            _ => unreachable!(),
+        };
+        if span == 0 {
+            0.0
+        } else {
+            a.abs_diff(b) as f64 / span as f64
+        }
+    }
+
+    fn quantile(&self, u: f64) -> Value {
+        match self {
+            Integer::Sequence(start, end) => {
+                let count = (end - start) as f64 + 1.0;
+                Value::Integer(start + ((u * count) as u32).min(end - start))
+            }
+            Integer::Candidates(candidates) => Value::Index(
+                ((u * candidates.len() as f64) as usize).min(candidates.len().saturating_sub(1)),
+            ),
+        }
+    }
+}
+
+impl Integer {
+    pub(crate) fn blend(&self, a: Value, b: Value, alpha: f64) -> Value {
+        match (self, a, b) {
+            (Integer::Sequence(start, end), Value::Integer(a), Value::Integer(b)) => {
+                let (low, high) = (a.min(b) as f64, a.max(b) as f64);
+                let margin = (high - low) * alpha;
+                let x = rand::random_range(low - margin..=high + margin);
+                Value::Integer(x.round().clamp(*start as f64, *end as f64) as u32)
+            }
+            _ => self.crossover(a, b),
+        }
+    }
+
+    pub(crate) fn simulated_binary(&self, a: Value, b: Value, eta: f64) -> Value {
+        match (self, a, b) {
+            (Integer::Sequence(start, end), Value::Integer(a), Value::Integer(b)) => {
+                let u = rand::random::<f64>();
+                let beta = if u <= 0.5 {
+                    (2.0 * u).powf(1.0 / (eta + 1.0))
+                } else {
+                    (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (eta + 1.0))
+                };
+                let (a, b) = if rand::random() { (a, b) } else { (b, a) };
+                let x = 0.5 * ((1.0 + beta) * a as f64 + (1.0 - beta) * b as f64);
+                Value::Integer(x.round().clamp(*start as f64, *end as f64) as u32)
+            }
+            _ => self.crossover(a, b),
+        }
+    }
 }
 
 pub(crate) struct Switch {}
@@ -111,6 +205,29 @@ impl Space for Switch {
             }
         }
     }
+
+    fn values(&self) -> Vec<Value> {
+        vec![Value::Switch(false), Value::Switch(true)]
+    }
+
+    fn size(&self) -> u64 {
+        2
+    }
+
+    fn neighbours(&self, value: Value, _: u32) -> Vec<Value> {
+        match value {
+            Value::Switch(b) => vec![Value::Switch(!b)],
+            _ => unreachable!(),
+        }
+    }
+
+    fn distance(&self, a: Value, b: Value) -> f64 {
+        if a == b { 0.0 } else { 1.0 }
+    }
+
+    fn quantile(&self, u: f64) -> Value {
+        Value::Switch(u >= 0.5)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -136,10 +253,33 @@ impl Space for Keyword {
     }
 
     fn mutate(&self, mutations: &Mutation, code: &mut Value) {
-        if let Some(options) = &mutations.keyword {
-            if rand::random_bool(options.probability.value) {
-                *code = self.random();
-            }
+        if let Some(options) = &mutations.keyword
+            && rand::random_bool(options.probability.value)
+        {
+            *code = self.random();
         }
     }
+
+    fn values(&self) -> Vec<Value> {
+        (0..self.0.len()).map(Value::Index).collect()
+    }
+
+    fn size(&self) -> u64 {
+        self.0.len() as u64
+    }
+
+    fn neighbours(&self, value: Value, _: u32) -> Vec<Value> {
+        (0..self.0.len())
+            .map(Value::Index)
+            .filter(|other| *other != value)
+            .collect()
+    }
+
+    fn distance(&self, a: Value, b: Value) -> f64 {
+        if a == b { 0.0 } else { 1.0 }
+    }
+
+    fn quantile(&self, u: f64) -> Value {
+        Value::Index(((u * self.0.len() as f64) as usize).min(self.0.len().saturating_sub(1)))
+    }
 }
@@ -0,0 +1,199 @@
+use crate::{configuration::Sampler, individual::Individual, parameter::Profile};
+use rand::{Rng, seq::SliceRandom};
+
+const BITS: usize = 32;
+
+// Joe-Kuo direction numbers (new-joe-kuo-6.21201) from the second dimension
+// on: degree of the primitive polynomial, its inner coefficients and the
+// initial direction numbers. The first dimension is the van der Corput sequence.
+const DIRECTIONS: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+
+// the number of parameters Sobol sampling supports
+pub(crate) const SOBOL_DIMENSIONS: usize = DIRECTIONS.len() + 1;
+
+pub(crate) fn sample(sampler: &Sampler, profile: &Profile, n: usize) -> Vec<Individual> {
+    let dimensions = profile.0.len();
+    let points = match sampler {
+        Sampler::Random => {
+            return (0..n).map(|_| Individual::random(profile)).collect();
+        }
+        Sampler::Maximin { candidates } => return maximin(profile, n, *candidates),
+        Sampler::LatinHypercube => latin_hypercube(n, dimensions),
+        Sampler::Sobol => sobol(n, dimensions),
+    };
+    points
+        .iter()
+        .map(|point| profile.unit_to_individual(point))
+        .collect()
+}
+
+// one point in each of the `n` strata of every dimension
+fn latin_hypercube(n: usize, dimensions: usize) -> Vec<Vec<f64>> {
+    let mut rng = rand::rng();
+    let mut points = vec![Vec::with_capacity(dimensions); n];
+    for _ in 0..dimensions {
+        let mut strata = (0..n).collect::<Vec<_>>();
+        strata.shuffle(&mut rng);
+        for (point, stratum) in points.iter_mut().zip(strata) {
+            point.push((stratum as f64 + rng.random::<f64>()) / n as f64);
+        }
+    }
+    points
+}
+
+fn directions(dimension: usize) -> [u32; BITS] {
+    let mut v = [0; BITS];
+    if dimension == 0 {
+        for (k, v) in v.iter_mut().enumerate() {
+            *v = 1 << (BITS - 1 - k);
+        }
+        return v;
+    }
+    let (s, a, m) = DIRECTIONS[dimension - 1];
+    let s = s as usize;
+    for k in 0..BITS {
+        v[k] = if k < s {
+            m[k] << (BITS - 1 - k)
+        } else {
+            let mut x = v[k - s] ^ (v[k - s] >> s);
+            for j in 1..s {
+                if (a >> (s - 1 - j)) & 1 == 1 {
+                    x ^= v[k - j];
+                }
+            }
+            x
+        };
+    }
+    v
+}
+
+// Applies a random lower-triangular binary matrix with a unit diagonal to the
+// digits of every direction number, most significant digit first.
+fn scramble(v: &mut [u32; BITS], rng: &mut impl Rng) {
+    let rows = (0..BITS)
+        .map(|i| {
+            let diagonal = 1u32 << (BITS - 1 - i);
+            let above = !(diagonal | (diagonal - 1));
+            diagonal | (rng.random::<u32>() & above)
+        })
+        .collect::<Vec<_>>();
+    for x in v.iter_mut() {
+        *x = rows.iter().enumerate().fold(0, |y, (i, row)| {
+            y | ((row & *x).count_ones() & 1) << (BITS - 1 - i)
+        });
+    }
+}
+
+// Sobol points with scrambled direction numbers and a random digital shift
+fn sobol(n: usize, dimensions: usize) -> Vec<Vec<f64>> {
+    let mut rng = rand::rng();
+    let mut directions = (0..dimensions).map(directions).collect::<Vec<_>>();
+    let mut shift = vec![0u32; dimensions];
+    for (v, x) in directions.iter_mut().zip(&mut shift) {
+        scramble(v, &mut rng);
+        *x = rng.random();
+    }
+    gray_code(n, &directions, shift)
+}
+
+// points of the digital sequence with the given direction numbers in Gray
+// code order, starting from `x`
+fn gray_code(n: usize, directions: &[[u32; BITS]], mut x: Vec<u32>) -> Vec<Vec<f64>> {
+    let scale = 2f64.powi(-(BITS as i32));
+    let mut points = Vec::with_capacity(n);
+    for i in 0..n {
+        if i > 0 {
+            let c = (i - 1).trailing_ones() as usize;
+            for (x, v) in x.iter_mut().zip(directions) {
+                *x ^= v[c.min(BITS - 1)];
+            }
+        }
+        points.push(x.iter().map(|x| *x as f64 * scale).collect());
+    }
+    points
+}
+
+// Greedy maximin design: each individual is the candidate farthest from the
+// nearest of those already chosen.
+fn maximin(profile: &Profile, n: usize, candidates: usize) -> Vec<Individual> {
+    let mut chosen = Vec::<Individual>::with_capacity(n);
+    while chosen.len() < n {
+        let best = (0..candidates.max(1))
+            .map(|_| {
+                let candidate = Individual::random(profile);
+                let nearest = chosen
+                    .iter()
+                    .map(|other| profile.distance(&candidate.parameters, &other.parameters))
+                    .fold(f64::INFINITY, f64::min);
+                (candidate, nearest)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        chosen.push(best.0);
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latin_hypercube() {
+        let points = latin_hypercube(8, 3);
+        for d in 0..3 {
+            let mut strata = points
+                .iter()
+                .map(|point| (point[d] * 8.0) as usize)
+                .collect::<Vec<_>>();
+            strata.sort();
+            assert_eq!(strata, (0..8).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_sobol() {
+        let unscrambled = (0..2).map(directions).collect::<Vec<_>>();
+        let points = gray_code(4, &unscrambled, vec![0; 2]);
+        assert_eq!(
+            points,
+            vec![
+                vec![0.0, 0.0],
+                vec![0.5, 0.5],
+                vec![0.75, 0.25],
+                vec![0.25, 0.75],
+            ]
+        );
+        // every dyadic interval of length 1/8 holds exactly one point
+        let points = sobol(8, SOBOL_DIMENSIONS);
+        for d in 0..SOBOL_DIMENSIONS {
+            let mut cells = points
+                .iter()
+                .map(|point| (point[d] * 8.0) as usize)
+                .collect::<Vec<_>>();
+            cells.sort();
+            assert_eq!(cells, (0..8).collect::<Vec<_>>());
+        }
+    }
+}
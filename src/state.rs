@@ -1,6 +1,14 @@
-use crate::{configuration::Hyperparameters, genetic, individual::Individual, parameter::Profile};
+use crate::{
+    configuration::{Hyperparameters, Mutation},
+    genetic,
+    individual::Individual,
+    parameter::{Combination, Profile},
+    sampler,
+};
+use fxhash::FxHashMap;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 #[derive(Serialize, Deserialize)]
 pub(crate) struct State {
@@ -8,6 +16,23 @@ pub(crate) struct State {
     pub(crate) count: usize,
     pub(crate) hyperparameters: Hyperparameters,
     pub(crate) population: Vec<Individual>,
+    #[serde(default)]
+    pub(crate) restarts: usize,
+    #[serde(default)]
+    pub(crate) boosted: Option<Boosted>,
+    // quality of each operator of the bandit, empty before the first update
+    #[serde(default)]
+    pub(crate) qualities: Vec<f64>,
+    // every valid individual evaluated since start, for the surrogate
+    #[serde(default)]
+    pub(crate) archive: FxHashMap<Arc<str>, (Combination, f64)>,
+}
+
+// mutation settings to restore once a boost after a restart runs out
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Boosted {
+    pub(crate) remaining: usize,
+    pub(crate) mutate: Mutation,
 }
 
 impl State {
@@ -24,9 +49,11 @@ impl State {
                 })
                 .collect::<Vec<_>>()
         } else {
-            population
-                .map(|_| Individual::random(profile))
-                .collect::<Vec<_>>()
+            sampler::sample(
+                &hyperparameters.sampler,
+                profile,
+                hyperparameters.initial_population,
+            )
         };
 
         State {
@@ -34,10 +61,21 @@ impl State {
             count: 0,
             hyperparameters,
             population,
+            restarts: 0,
+            boosted: None,
+            qualities: Vec::new(),
+            archive: FxHashMap::default(),
         }
     }
 
     pub(crate) fn step(&mut self) {
         self.hyperparameters.step();
+        if let Some(boosted) = &mut self.boosted {
+            boosted.mutate.step();
+            boosted.remaining = boosted.remaining.saturating_sub(1);
+            if boosted.remaining == 0 {
+                self.hyperparameters.mutate = self.boosted.take().unwrap().mutate;
+            }
+        }
     }
 }
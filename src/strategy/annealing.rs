@@ -0,0 +1,208 @@
+use crate::{
+    configuration::{Annealing as Options, Configuration, Mutation, Termination},
+    genetic,
+    individual::{Fitness, Individual},
+    strategy::{self, Progress, Strategy},
+    utils::scaling::Real,
+};
+use anyhow::anyhow;
+use fxhash::FxHashSet;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    step: usize,
+    // steps since the best energy improved
+    count: usize,
+    // steps since the trajectory was (re)started without improvement
+    stagnation: usize,
+    restarts: usize,
+    temperature: Real,
+    mutate: Mutation,
+    terminate: Termination,
+    current: Option<Individual>,
+    // fitness of the current and of the best individual so far
+    energy: Option<f64>,
+    best: Option<f64>,
+}
+
+pub(crate) struct Annealing<'a> {
+    configuration: &'a Configuration,
+    options: &'a Options,
+    state: State,
+    finished: bool,
+}
+
+impl<'a> Annealing<'a> {
+    pub(crate) fn new(
+        configuration: &'a Configuration,
+        options: &'a Options,
+        checkpoint: Option<serde_json::Value>,
+    ) -> anyhow::Result<Self> {
+        if options.temperature.value <= 0.0 {
+            return Err(anyhow!("Initial temperature must be greater than 0"));
+        }
+        if options.neighbours == 0 {
+            return Err(anyhow!("Number of neighbours must be greater than 0"));
+        }
+
+        let state = if let Some(checkpoint) = checkpoint {
+            serde_json::from_value(checkpoint)?
+        } else {
+            State {
+                step: 1,
+                count: 0,
+                stagnation: 0,
+                restarts: 0,
+                temperature: options.temperature.clone(),
+                mutate: options.mutate.clone(),
+                terminate: options.terminate.clone(),
+                current: None,
+                energy: None,
+                best: None,
+            }
+        };
+
+        Ok(Annealing {
+            configuration,
+            options,
+            state,
+            finished: false,
+        })
+    }
+
+    // Metropolis criterion on the worsening relative to the current energy,
+    // so that the temperature does not depend on the unit of the fitness.
+    fn accept(&self, energy: f64) -> bool {
+        let current = match self.state.energy {
+            Some(current)
+                if self
+                    .configuration
+                    .direction
+                    .compare(energy, current)
+                    .is_lt() =>
+            {
+                current
+            }
+            _ => return true,
+        };
+        let worsening = (energy - current).abs() / current.abs().max(f64::EPSILON);
+        rand::rng().random::<f64>() < (-worsening / self.state.temperature.value).exp()
+    }
+
+    fn improves(&self, energy: f64) -> bool {
+        self.state
+            .best
+            .is_none_or(|best| self.configuration.direction.compare(energy, best).is_gt())
+    }
+}
+
+impl Strategy for Annealing<'_> {
+    fn ask(&mut self) -> Vec<Individual> {
+        if self.finished {
+            return Vec::new();
+        }
+        let profile = &self.configuration.profile;
+        match &self.state.current {
+            // the configured individual is only tried at the very first step
+            None => {
+                let initial = match &self.options.initial {
+                    Some(initial) if self.state.step == 1 => {
+                        let mut initial = profile.string_to_individual(initial);
+                        profile.adjust(&mut initial);
+                        initial
+                    }
+                    _ => Individual::random(profile),
+                };
+                vec![initial]
+            }
+            // distinct neighbours, none at all ending the search when the
+            // mutation cannot move away from the current individual
+            Some(current) => {
+                let mut seen = FxHashSet::default();
+                seen.insert(current.id.clone());
+                let (batch, _) = strategy::draw_unseen(self.options.neighbours, &mut seen, |_| {
+                    let mut neighbour = Individual::new(current.parameters.clone());
+                    genetic::mutate(profile, &self.state.mutate, &mut neighbour);
+                    neighbour
+                });
+                batch
+            }
+        }
+    }
+
+    fn tell(&mut self, evaluated: Vec<Individual>, progress: &mut Progress) {
+        let boundaries = strategy::boundaries(self.configuration, &evaluated);
+        let (best, _) = boundaries;
+
+        // without a current individual (at the start or after a restart) the
+        // first valid one is always accepted
+        if best.is_finite() {
+            let candidate = evaluated
+                .into_iter()
+                .find(|individual| individual.fitness == Fitness::Valid(best))
+                .unwrap();
+            if self.accept(best) {
+                self.state.current = Some(candidate);
+                self.state.energy = Some(best);
+            }
+        }
+
+        if let Some(summary) = progress.summary(boundaries) {
+            progress.summarize(format!("Step #{}", self.state.step), summary);
+        }
+        if let Some(energy) = self.state.energy {
+            writeln!(
+                progress.log,
+                "Temperature: {} Current: {}",
+                self.state.temperature.value, energy
+            )
+            .unwrap();
+        }
+
+        if best.is_finite() && self.improves(best) {
+            self.state.best = Some(best);
+            self.state.count = 0;
+            self.state.stagnation = 0;
+        } else {
+            self.state.count += 1;
+            self.state.stagnation += 1;
+        }
+
+        // termination check
+        let state = &mut self.state;
+        let finished = progress.terminate(
+            &mut state.terminate,
+            self.configuration.direction,
+            state.step,
+            state.count,
+            state.best,
+        );
+        state.step += 1;
+        if finished {
+            self.finished = true;
+            return;
+        }
+
+        if let Some(restart) = self.options.restart
+            && self.state.stagnation >= restart
+        {
+            writeln!(progress.log, "Restarting after {} steps", restart).unwrap();
+            self.state.restarts += 1;
+            self.state.stagnation = 0;
+            self.state.current = None;
+            self.state.energy = None;
+            self.state.temperature = self.options.temperature.clone();
+            self.state.mutate = self.options.mutate.clone();
+        } else if self.state.current.is_some() {
+            self.state.temperature.step();
+            self.state.mutate.step();
+        }
+        writeln!(progress.log).unwrap();
+    }
+
+    fn checkpoint(&self) -> serde_json::Value {
+        serde_json::to_value(&self.state).unwrap()
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{
+    configuration::{Configuration, Differential as Options, Mutation, Termination},
+    genetic,
+    individual::{Fitness, Individual},
+    parameter::{Specification, Value, space},
+    strategy::{Progress, Strategy},
+};
+use anyhow::anyhow;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    generation: usize,
+    // generations since the best fitness improved
+    count: usize,
+    mutate: Mutation,
+    terminate: Termination,
+    // empty until the initial population is evaluated; the fitness is kept
+    // alongside since individuals are checkpointed without it
+    population: Vec<(Individual, Fitness)>,
+    best: Option<f64>,
+}
+
+pub(crate) struct Differential<'a> {
+    configuration: &'a Configuration,
+    options: &'a Options,
+    state: State,
+    finished: bool,
+}
+
+impl<'a> Differential<'a> {
+    pub(crate) fn new(
+        configuration: &'a Configuration,
+        options: &'a Options,
+        checkpoint: Option<serde_json::Value>,
+    ) -> anyhow::Result<Self> {
+        if options.population < 4 {
+            return Err(anyhow!("Population size must be at least 4"));
+        }
+        if options.weight <= 0.0 {
+            return Err(anyhow!("Differential weight must be greater than 0"));
+        }
+        if !(0.0..=1.0).contains(&options.rate) {
+            return Err(anyhow!("Crossover rate must be in [0, 1]"));
+        }
+
+        let state = if let Some(checkpoint) = checkpoint {
+            serde_json::from_value(checkpoint)?
+        } else {
+            State {
+                generation: 1,
+                count: 0,
+                mutate: options.mutate.clone(),
+                terminate: options.terminate.clone(),
+                population: Vec::new(),
+                best: None,
+            }
+        };
+
+        Ok(Differential {
+            configuration,
+            options,
+            state,
+            finished: false,
+        })
+    }
+
+    // Trial vector for the `target`-th individual: the sequence parameters
+    // come from a + F (b - c) with binomial crossover, the others from the
+    // crossover of the target and a followed by mutation.
+    fn trial(&self, target: usize) -> Individual {
+        let profile = &self.configuration.profile;
+        let population = &self.state.population;
+        let mut rng = rand::rng();
+        let donors = rand::seq::index::sample(&mut rng, population.len() - 1, 3)
+            .into_iter()
+            .map(|index| {
+                let index = if index < target { index } else { index + 1 };
+                &population[index].0.parameters
+            })
+            .collect::<Vec<_>>();
+        let (a, b, c) = (donors[0], donors[1], donors[2]);
+        let target = &population[target].0.parameters;
+
+        let is_sequence = |specification: &Specification| {
+            matches!(
+                specification,
+                Specification::Integer {
+                    space: space::Integer::Sequence(_, _),
+                    ..
+                }
+            )
+        };
+        // at least one sequence parameter comes from the mutant
+        let sequences = profile.0.values().filter(|s| is_sequence(s)).count();
+        let forced = (sequences > 0).then(|| rng.random_range(0..sequences));
+
+        let mut parameters = target.clone();
+        let mut k = 0;
+        for (name, specification) in &profile.0 {
+            let space = specification.get_space();
+            let value = parameters.get_mut(name).unwrap();
+            match (specification.as_ref(), a[name], b[name], c[name]) {
+                (
+                    Specification::Integer {
+                        space: space::Integer::Sequence(start, end),
+                        ..
+                    },
+                    Value::Integer(a),
+                    Value::Integer(b),
+                    Value::Integer(c),
+                ) => {
+                    if forced == Some(k) || rng.random_bool(self.options.rate) {
+                        let x = a as f64 + self.options.weight * (b as f64 - c as f64);
+                        *value = Value::Integer(x.round().clamp(*start as f64, *end as f64) as u32);
+                    }
+                    k += 1;
+                }
+                _ => {
+                    *value = space.crossover(*value, a[name]);
+                    space.mutate(&self.state.mutate, value);
+                }
+            }
+        }
+
+        let mut trial = Individual::new(parameters);
+        profile.adjust(&mut trial);
+        trial
+    }
+}
+
+impl Strategy for Differential<'_> {
+    fn ask(&mut self) -> Vec<Individual> {
+        if self.finished {
+            return Vec::new();
+        }
+        if self.state.population.is_empty() {
+            let profile = &self.configuration.profile;
+            return (0..self.options.population)
+                .map(|_| Individual::random(profile))
+                .collect();
+        }
+        (0..self.state.population.len())
+            .map(|target| self.trial(target))
+            .collect()
+    }
+
+    fn tell(&mut self, evaluated: Vec<Individual>, progress: &mut Progress) {
+        let direction = self.configuration.direction;
+        let criterion = self.configuration.criterion;
+
+        // each trial replaces its target unless it is worse
+        if self.state.population.is_empty() {
+            self.state.population = evaluated
+                .into_iter()
+                .map(|individual| {
+                    let fitness = individual.fitness;
+                    (individual, fitness)
+                })
+                .collect();
+        } else {
+            for (target, trial) in self.state.population.iter_mut().zip(evaluated) {
+                let replace = match (target.1, trial.fitness) {
+                    (_, Fitness::Invalid | Fitness::Unknown) => false,
+                    (Fitness::Valid(_), Fitness::Valid(_)) => direction
+                        .compare(
+                            trial.fitness.into_f64(criterion),
+                            target.1.into_f64(criterion),
+                        )
+                        .is_ge(),
+                    _ => true,
+                };
+                if replace {
+                    let fitness = trial.fitness;
+                    *target = (trial, fitness);
+                }
+            }
+        }
+
+        let boundaries = direction.boundaries(
+            self.state
+                .population
+                .iter()
+                .map(|(_, fitness)| fitness.into_f64(criterion))
+                .filter(|x| x.is_finite()),
+        );
+        let (best, _) = boundaries;
+        if let Some(mut summary) = progress.summary(boundaries) {
+            let individuals = self
+                .state
+                .population
+                .iter()
+                .map(|(individual, _)| individual.clone())
+                .collect::<Vec<_>>();
+            summary.diversity = Some(genetic::diversity(
+                &self.configuration.profile,
+                &individuals,
+            ));
+            progress.summarize(format!("Generation #{}", self.state.generation), summary);
+        }
+
+        if best.is_finite()
+            && self
+                .state
+                .best
+                .is_none_or(|previous| direction.compare(best, previous).is_gt())
+        {
+            self.state.best = Some(best);
+            self.state.count = 0;
+        } else {
+            self.state.count += 1;
+        }
+
+        // termination check
+        let state = &mut self.state;
+        let finished = progress.terminate(
+            &mut state.terminate,
+            direction,
+            state.generation,
+            state.count,
+            state.best,
+        );
+        state.generation += 1;
+        if finished {
+            self.finished = true;
+            return;
+        }
+
+        self.state.mutate.step();
+        writeln!(progress.log).unwrap();
+    }
+
+    fn checkpoint(&self) -> serde_json::Value {
+        serde_json::to_value(&self.state).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{configuration, output::Output};
+
+    #[test]
+    fn test_checkpoint() {
+        let configuration = serde_json::from_str::<Configuration>(
+            r#"{"direction": "minimize", "criterion": "median",
+                "profile": {"x": {"Integer": {"space": {"Sequence": [0, 100]}}}},
+                "helper": {"pre": "pre", "post": "post"}, "runner": "run", "compiler": "cc",
+                "strategy": {"Differential": {"population": 4}}}"#,
+        )
+        .unwrap();
+        let configuration::Strategy::Differential(options) = &configuration.strategy else {
+            unreachable!()
+        };
+        let mut output = Output::new(configuration.direction, &[], 10);
+        let mut log = Vec::new();
+        let unit = None;
+
+        let mut differential = Differential::new(&configuration, options, None).unwrap();
+        let mut initial = differential.ask();
+        for (i, individual) in initial.iter_mut().enumerate() {
+            individual.fitness = Fitness::Valid(i as f64);
+        }
+        let first = initial.clone();
+        let mut progress = Progress::new(&mut output, &mut log, &unit);
+        differential.tell(initial, &mut progress);
+
+        // resumed, every target keeps its place against worse trials
+        let checkpoint = differential.checkpoint();
+        let mut differential =
+            Differential::new(&configuration, options, Some(checkpoint)).unwrap();
+        let mut trials = differential.ask();
+        for trial in &mut trials {
+            trial.fitness = Fitness::Valid(100.0);
+        }
+        let mut progress = Progress::new(&mut output, &mut log, &unit);
+        differential.tell(trials, &mut progress);
+        let population = &differential.state.population;
+        assert!(
+            population
+                .iter()
+                .zip(&first)
+                .all(|((individual, fitness), expected)| {
+                    individual == expected && *fitness == expected.fitness
+                })
+        );
+    }
+}
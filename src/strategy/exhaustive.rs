@@ -0,0 +1,181 @@
+use crate::{
+    configuration::{Configuration, Exhaustive as Options},
+    individual::Individual,
+    parameter::Value,
+    strategy::{self, Progress, Strategy},
+};
+use anyhow::anyhow;
+use fxhash::FxHashSet;
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+
+#[derive(Serialize, Deserialize, Default)]
+struct State {
+    batch: usize,
+    // index of the next combination to evaluate
+    position: usize,
+}
+
+pub(crate) struct Exhaustive<'a> {
+    configuration: &'a Configuration,
+    options: &'a Options,
+    state: State,
+    // distinct combinations after adjustment, in enumeration order
+    combinations: Vec<Individual>,
+    // size of the grid before adjustment, reported with the first batch
+    total: u128,
+    reported: bool,
+}
+
+impl<'a> Exhaustive<'a> {
+    pub(crate) fn new(
+        configuration: &'a Configuration,
+        options: &'a Options,
+        checkpoint: Option<serde_json::Value>,
+    ) -> anyhow::Result<Self> {
+        if options.batch == 0 {
+            return Err(anyhow!("Batch size must be greater than 0"));
+        }
+
+        // check the size before any value is enumerated
+        let profile = &configuration.profile;
+        let total = profile.0.values().fold(1u128, |total, specification| {
+            total.saturating_mul(specification.get_space().size() as u128)
+        });
+        if total > options.limit as u128 {
+            return Err(anyhow!(
+                "Search space has {} combinations, exceeding the limit of {}",
+                total,
+                options.limit
+            ));
+        }
+        let (names, domains): (Vec<Arc<str>>, Vec<Vec<Value>>) = profile
+            .0
+            .iter()
+            .map(|(name, specification)| (name.clone(), specification.get_space().values()))
+            .unzip();
+
+        // walk the Cartesian product like an odometer
+        let mut combinations = Vec::new();
+        let mut seen = FxHashSet::default();
+        let mut digits = vec![0; domains.len()];
+        // ties in the adjustment are broken the same way on every run, so that
+        // a resumed search enumerates the same combinations in the same order
+        let mut rng = StdRng::seed_from_u64(0);
+        if domains.iter().all(|domain| !domain.is_empty()) {
+            loop {
+                let mut individual = Individual::new(
+                    names
+                        .iter()
+                        .zip(&domains)
+                        .zip(&digits)
+                        .map(|((name, domain), &digit)| (name.clone(), domain[digit]))
+                        .collect::<BTreeMap<_, _>>(),
+                );
+                profile.adjust_with(&mut individual, &mut rng);
+                if seen.insert(individual.id.clone()) {
+                    combinations.push(individual);
+                }
+
+                let carry = digits.iter_mut().zip(&domains).all(|(digit, domain)| {
+                    *digit += 1;
+                    if *digit < domain.len() {
+                        return false;
+                    }
+                    *digit = 0;
+                    true
+                });
+                if carry {
+                    break;
+                }
+            }
+        }
+        let state = if let Some(checkpoint) = checkpoint {
+            serde_json::from_value(checkpoint)?
+        } else {
+            State::default()
+        };
+
+        Ok(Exhaustive {
+            configuration,
+            options,
+            state,
+            combinations,
+            total,
+            reported: false,
+        })
+    }
+}
+
+impl Strategy for Exhaustive<'_> {
+    fn ask(&mut self) -> Vec<Individual> {
+        let start = self.state.position.min(self.combinations.len());
+        let end = (start + self.options.batch).min(self.combinations.len());
+        self.combinations[start..end].to_vec()
+    }
+
+    fn tell(&mut self, evaluated: Vec<Individual>, progress: &mut Progress) {
+        self.state.batch += 1;
+        self.state.position += evaluated.len();
+
+        if !self.reported {
+            writeln!(
+                progress.log,
+                "Search space: {} combinations, {} distinct after adjustment",
+                self.total,
+                self.combinations.len()
+            )
+            .unwrap();
+            self.reported = true;
+        }
+
+        let boundaries = strategy::boundaries(self.configuration, &evaluated);
+        if let Some(summary) = progress.summary(boundaries) {
+            progress.summarize(format!("Batch #{}", self.state.batch), summary);
+        }
+        writeln!(
+            progress.log,
+            "{}/{}\n",
+            self.state.position,
+            self.combinations.len()
+        )
+        .unwrap();
+    }
+
+    fn checkpoint(&self) -> serde_json::Value {
+        serde_json::to_value(&self.state).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration;
+
+    #[test]
+    fn test_deterministic_adjustment() {
+        // odd values lie halfway between two multiples of 2
+        let configuration = serde_json::from_str::<Configuration>(
+            r#"{"direction": "minimize", "criterion": "median",
+                "profile": {"x": {"Integer": {"space": {"Sequence": [0, 41]},
+                    "condition": {"MultipleOf": {"Constant": {"Integer": 2}}}}}},
+                "helper": {"pre": "pre", "post": "post"}, "runner": "run", "compiler": "cc",
+                "strategy": {"Exhaustive": {}}}"#,
+        )
+        .unwrap();
+        let configuration::Strategy::Exhaustive(options) = &configuration.strategy else {
+            unreachable!()
+        };
+        let ids = || {
+            let exhaustive = Exhaustive::new(&configuration, options, None).unwrap();
+            exhaustive
+                .combinations
+                .iter()
+                .map(|individual| individual.id.clone())
+                .collect::<Vec<_>>()
+        };
+        let first = ids();
+        assert!((0..10).all(|_| ids() == first));
+    }
+}
@@ -0,0 +1,766 @@
+use crate::{
+    configuration::{Configuration, Hyperparameters, Reseed, Sampler, Selection, Surrogate},
+    direction::Direction,
+    genetic::{self, GenerationSummary, Operator, Statistics},
+    individual::{Fitness, Individual},
+    sampler,
+    state::{Boosted, State},
+    strategy::{Progress, Strategy},
+};
+use anyhow::anyhow;
+use fxhash::FxHashSet;
+use rand::seq::SliceRandom;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::hint;
+
+enum Phase {
+    Population,
+    Children {
+        // (ranks, crowding distances) with multiple objectives
+        crowded: Option<(Vec<usize>, Vec<f64>)>,
+        // (parent selection weight, index)
+        flattened: Vec<(f64, usize)>,
+        holes: Vec<usize>,
+        children: Vec<Individual>,
+    },
+    Finished,
+}
+
+pub(crate) struct Genetic<'a> {
+    configuration: &'a Configuration,
+    state: State,
+    phase: Phase,
+    best_overall: f64,
+    // prefix of the generation summary title
+    label: String,
+    // children rejected by the surrogate since the last summary
+    screened: usize,
+    // operator of each child of the latest batch, with the bandit
+    operators: Vec<Option<Operator>>,
+    // per operator, since the last summary
+    statistics: [Statistics; Operator::ALL.len()],
+}
+
+impl<'a> Genetic<'a> {
+    pub(crate) fn new(
+        configuration: &'a Configuration,
+        checkpoint: Option<serde_json::Value>,
+    ) -> anyhow::Result<Self> {
+        Self::with(
+            configuration,
+            &configuration.hyperparameters,
+            String::new(),
+            checkpoint,
+        )
+    }
+
+    pub(crate) fn with(
+        configuration: &'a Configuration,
+        hyperparameters: &Hyperparameters,
+        label: String,
+        checkpoint: Option<serde_json::Value>,
+    ) -> anyhow::Result<Self> {
+        if hyperparameters.initial_population <= 1 {
+            return Err(anyhow!("Initial population size must be greater than 1"));
+        }
+        if hyperparameters.generate.value == 0 {
+            return Err(anyhow!("Number of each generation must be greater than 0"));
+        }
+        // a goal alone only postpones `endure`, so it never stops the search
+        let terminate = &hyperparameters.terminate;
+        if terminate.limit.is_none() && terminate.endure.is_none() {
+            return Err(anyhow!(
+                "Termination requires a generation limit or an endure count"
+            ));
+        }
+
+        match hyperparameters.selection {
+            Selection::Tournament { size: 0 } => {
+                return Err(anyhow!("Tournament size must be greater than 0"));
+            }
+            Selection::Truncation { fraction } if fraction <= 0.0 || fraction > 1.0 => {
+                return Err(anyhow!("Truncation fraction must be in (0, 1]"));
+            }
+            _ => {}
+        }
+
+        if let Some(restart) = &hyperparameters.restart {
+            if restart.stagnation == 0 {
+                return Err(anyhow!("Restart stagnation must be greater than 0"));
+            }
+            if let Some(boost) = &restart.boost
+                && boost.factor <= 0.0
+            {
+                return Err(anyhow!("Boost factor must be greater than 0"));
+            }
+        }
+
+        if let Some(surrogate) = &hyperparameters.surrogate
+            && (surrogate.neighbours == 0 || surrogate.quantile <= 0.0 || surrogate.quantile > 1.0)
+        {
+            return Err(anyhow!(
+                "Surrogate requires at least one neighbour and a quantile in (0, 1]"
+            ));
+        }
+
+        if let Some(bandit) = &hyperparameters.bandit
+            && (bandit.minimum < 0.0
+                || bandit.minimum * Operator::ALL.len() as f64 >= 1.0
+                || bandit.adaptation <= 0.0
+                || bandit.adaptation > 1.0)
+        {
+            return Err(anyhow!(
+                "Bandit requires a minimum probability below 1/{} and an adaptation rate in (0, 1]",
+                Operator::ALL.len()
+            ));
+        }
+
+        if let Some(sharing) = &hyperparameters.sharing
+            && sharing.radius <= 0.0
+        {
+            return Err(anyhow!("Sharing radius must be greater than 0"));
+        }
+
+        if let Sampler::Sobol = hyperparameters.sampler
+            && hyperparameters.initial.is_none()
+            && configuration.profile.0.len() > sampler::SOBOL_DIMENSIONS
+        {
+            return Err(anyhow!(
+                "Sobol sampling supports up to {} parameters",
+                sampler::SOBOL_DIMENSIONS
+            ));
+        }
+
+        let state = if let Some(checkpoint) = checkpoint {
+            serde_json::from_value(checkpoint)?
+        } else {
+            State::new(hyperparameters, &configuration.profile)
+        };
+
+        Ok(Genetic {
+            configuration,
+            state,
+            phase: Phase::Population,
+            // Rust compiler somehow optimizes this function call or later is_gt() call in wrong way
+            // so wrap this call with black_box to prevent optimization
+            best_overall: hint::black_box(configuration.direction.worst()),
+            label,
+            screened: 0,
+            operators: Vec::new(),
+            statistics: Default::default(),
+        })
+    }
+
+    pub(crate) fn generation(&self) -> usize {
+        self.state.generation
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        matches!(self.phase, Phase::Finished)
+    }
+
+    // whether the population is complete and waits to be evaluated
+    pub(crate) fn is_settled(&self) -> bool {
+        matches!(self.phase, Phase::Population)
+    }
+
+    // the best `n` valid individuals of the population
+    pub(crate) fn elite(&self, n: usize) -> Vec<Individual> {
+        let direction = self.configuration.direction;
+        let mut valid = self
+            .state
+            .population
+            .iter()
+            .filter_map(|individual| match individual.fitness {
+                Fitness::Valid(fitness) => Some((fitness, individual)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        valid.sort_by(|(a, _), (b, _)| direction.compare(*b, *a));
+        valid
+            .into_iter()
+            .take(n)
+            .map(|(_, individual)| individual.clone())
+            .collect()
+    }
+
+    // Replaces the worst evaluated individuals with the immigrants, skipping
+    // those already present.
+    pub(crate) fn welcome(&mut self, immigrants: Vec<Individual>) {
+        let direction = self.configuration.direction;
+        // invalid individuals first, then from the worst
+        let mut order = self
+            .state
+            .population
+            .iter()
+            .enumerate()
+            .filter_map(|(index, individual)| match individual.fitness {
+                Fitness::Valid(fitness) => Some((Some(fitness), index)),
+                Fitness::Invalid => Some((None, index)),
+                Fitness::Unknown => None,
+            })
+            .collect::<Vec<_>>();
+        order.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) => direction.compare(*a, *b),
+            _ => a.is_some().cmp(&b.is_some()),
+        });
+        let mut holes = order.into_iter().map(|(_, index)| index);
+        for immigrant in immigrants {
+            if self.state.population.contains(&immigrant) {
+                continue;
+            }
+            if let Some(hole) = holes.next() {
+                self.state.population[hole] = immigrant;
+            }
+        }
+    }
+
+    fn remove_duplicates(&mut self) {
+        let mut seen = FxHashSet::default();
+        for individual in &mut self.state.population {
+            if seen.insert(individual.id.clone()) {
+                continue;
+            }
+
+            let mut replacement = Individual::random(&self.configuration.profile);
+            while !seen.insert(replacement.id.clone()) {
+                replacement = Individual::random(&self.configuration.profile);
+            }
+            *individual = replacement;
+        }
+    }
+
+    // Records the generation summary and checks for termination. Returns
+    // the phase in which children are generated, if the search goes on.
+    fn conclude(&mut self, progress: &mut Progress) -> Phase {
+        let state = &mut self.state;
+        let mut flattened = state
+            .population
+            .iter()
+            .enumerate()
+            .map(|(index, individual)| {
+                (
+                    individual.fitness.into_f64(self.configuration.criterion),
+                    index,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let iter = flattened.iter().map(|(x, _)| *x).filter(|x| x.is_finite());
+        let boundaries = self.configuration.direction.boundaries(iter);
+        let (best, worst) = boundaries;
+        if self
+            .configuration
+            .direction
+            .compare(best, self.best_overall)
+            .is_gt()
+        {
+            state.count = 0;
+            self.best_overall = best;
+        } else {
+            state.count += 1;
+        }
+
+        let last = state
+            .hyperparameters
+            .terminate
+            .is_reached(state.generation, state.count);
+        let restart = !last
+            && state
+                .hyperparameters
+                .restart
+                .as_ref()
+                .is_some_and(|restart| {
+                    state.count >= restart.stagnation
+                        && restart.limit.is_none_or(|limit| state.restarts < limit)
+                });
+
+        // record generation summary
+        let mut summary = GenerationSummary::new(progress.ranking.best().unwrap(), boundaries);
+        summary.diversity = Some(genetic::diversity(
+            &self.configuration.profile,
+            &state.population,
+        ));
+        if restart {
+            summary.restart = Some(state.restarts + 1);
+        }
+        if state.hyperparameters.surrogate.is_some() {
+            summary.screened = Some(self.screened);
+            self.screened = 0;
+        }
+        if state.hyperparameters.bandit.is_some() {
+            let statistics = std::mem::take(&mut self.statistics);
+            summary.operators = Some(Operator::ALL.into_iter().zip(statistics).collect());
+        }
+        progress.summarize(
+            format!("{}Generation #{}", self.label, state.generation),
+            summary,
+        );
+
+        // termination check
+        let finished = progress.terminate(
+            &mut state.hyperparameters.terminate,
+            self.configuration.direction,
+            state.generation,
+            state.count,
+            Some(self.best_overall),
+        );
+        state.generation += 1;
+        if finished {
+            return Phase::Finished;
+        }
+        writeln!(progress.log).unwrap();
+
+        if restart {
+            self.restart();
+            // the restart replaces `replace`, which would have advanced the schedules
+            self.state.step();
+            return Phase::Population;
+        }
+
+        let crowded = if self.configuration.objectives.is_empty() {
+            None
+        } else {
+            let directions = self
+                .configuration
+                .objectives
+                .iter()
+                .map(|objective| objective.direction)
+                .collect::<Vec<_>>();
+            let points = state
+                .population
+                .iter()
+                .map(Individual::point)
+                .collect::<Vec<_>>();
+            let ranks = genetic::non_dominated_sort(&points, &directions);
+            let distances = genetic::crowding_distance(&points, &ranks);
+            Some((ranks, distances))
+        };
+
+        let niches = match &state.hyperparameters.sharing {
+            Some(sharing) if crowded.is_none() => Some(genetic::niche_counts(
+                &self.configuration.profile,
+                sharing,
+                &state.population,
+            )),
+            _ => None,
+        };
+
+        let mut rng = rand::rng();
+
+        // select individuals to remove
+        // deletion weight = distance from best (worse individuals get larger
+        // weight, so they are more likely to be deleted); invalid individuals
+        // get the maximum weight to be deleted first.
+        let max_delete_weight = (best - worst).abs();
+        let mut inverted = flattened.clone();
+        for pair in &mut inverted {
+            if pair.0.is_infinite() {
+                pair.0 = max_delete_weight;
+                continue;
+            }
+
+            pair.0 = match self.configuration.direction {
+                Direction::Minimize => pair.0 - best,
+                Direction::Maximize => best - pair.0,
+            };
+        }
+        match self.configuration.direction {
+            Direction::Minimize => inverted.sort_by(|a, b| b.0.total_cmp(&a.0)),
+            Direction::Maximize => inverted.sort_by(|a, b| a.0.total_cmp(&b.0)),
+        }
+        inverted.truncate(inverted.len() - state.hyperparameters.remain);
+        if let Some(niches) = &niches {
+            for pair in &mut inverted {
+                pair.0 *= niches[pair.1];
+            }
+        }
+        inverted.shuffle(&mut rng);
+        let holes = if let Some((ranks, distances)) = &crowded {
+            // with multiple objectives, delete the worst in crowded-comparison order
+            let mut order = (0..state.population.len()).collect::<Vec<_>>();
+            order.sort_by(|&a, &b| genetic::crowded_compare(ranks, distances, a, b));
+            order.truncate(order.len() - state.hyperparameters.remain);
+            order.truncate(state.hyperparameters.delete.value);
+            order
+        } else {
+            genetic::select(
+                &state.hyperparameters.selection,
+                &inverted,
+                state.hyperparameters.delete.value,
+                true,
+            )
+        };
+        drop(inverted);
+
+        // parent selection weight = distance from worst (better individuals get
+        // larger weight, so they are more likely to be selected); invalid
+        // individuals get zero weight so they are never selected as parents.
+        for result in &mut flattened {
+            if result.0.is_infinite() {
+                result.0 = 0.0;
+                continue;
+            }
+
+            result.0 = match self.configuration.direction {
+                Direction::Minimize => worst - result.0,
+                Direction::Maximize => result.0 - worst,
+            };
+            if let Some(niches) = &niches {
+                result.0 /= niches[result.1];
+            }
+        }
+        flattened.shuffle(&mut rng);
+
+        Phase::Children {
+            crowded,
+            flattened,
+            holes,
+            children: Vec::with_capacity(state.hyperparameters.generate.value),
+        }
+    }
+
+    // Keeps the elite and re-seeds the rest of the population.
+    fn restart(&mut self) {
+        let restart = self.state.hyperparameters.restart.clone().unwrap();
+        let profile = &self.configuration.profile;
+        let elite = self.elite(restart.elite);
+        let reseeded = (elite.len()..self.state.population.len())
+            .into_par_iter()
+            .map(|index| match &restart.reseed {
+                Reseed::Mutated(mutation) if !elite.is_empty() => {
+                    let parent = &elite[index % elite.len()];
+                    let mut individual = Individual::new(parent.parameters.clone());
+                    genetic::mutate(profile, mutation, &mut individual);
+                    individual
+                }
+                _ => Individual::random(profile),
+            })
+            .collect::<Vec<_>>();
+
+        let state = &mut self.state;
+        state.population = elite;
+        state.population.extend(reseeded);
+        state.count = 0;
+        state.restarts += 1;
+
+        if let Some(boost) = &restart.boost {
+            // boost the original rates when still boosted by the previous restart
+            if let Some(boosted) = state.boosted.take() {
+                state.hyperparameters.mutate = boosted.mutate;
+            }
+            if boost.generations > 0 {
+                let mutate = state.hyperparameters.mutate.clone();
+                state.hyperparameters.mutate.boost(boost.factor);
+                state.boosted = Some(Boosted {
+                    remaining: boost.generations,
+                    mutate,
+                });
+            }
+        }
+    }
+
+    // Breeds `needed` children, rejecting those the surrogate predicts to be
+    // worse than the cutoff. Returns the children and the number rejected.
+    fn screen(
+        &self,
+        surrogate: &Surrogate,
+        needed: usize,
+        breed: impl Fn(usize) -> Vec<(Individual, Option<Operator>)>,
+    ) -> (Vec<(Individual, Option<Operator>)>, usize) {
+        let direction = self.configuration.direction;
+        let profile = &self.configuration.profile;
+        let mut valid = self
+            .state
+            .population
+            .iter()
+            .filter_map(|individual| match individual.fitness {
+                Fitness::Valid(fitness) => Some(fitness),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if valid.is_empty() {
+            return (breed(needed), 0);
+        }
+        valid.sort_by(|a, b| direction.compare(*b, *a));
+        let cutoff = valid[((valid.len() - 1) as f64 * surrogate.quantile).round() as usize];
+
+        let samples = self
+            .state
+            .archive
+            .values()
+            .map(|(parameters, fitness)| (parameters, *fitness))
+            .collect::<Vec<_>>();
+        let mut accepted = Vec::with_capacity(needed);
+        let mut rejected = Vec::new();
+        for _ in 0..surrogate.rounds.max(1) {
+            if accepted.len() == needed {
+                break;
+            }
+            let predicted = breed(needed - accepted.len())
+                .into_par_iter()
+                .map(|child| {
+                    let prediction = genetic::predict(
+                        profile,
+                        &samples,
+                        surrogate.neighbours,
+                        &child.0.parameters,
+                    );
+                    (child, prediction)
+                })
+                .collect::<Vec<_>>();
+            for (child, prediction) in predicted {
+                match prediction {
+                    Some(prediction) if direction.compare(prediction, cutoff).is_lt() => {
+                        rejected.push((child, prediction));
+                    }
+                    _ => accepted.push(child),
+                }
+            }
+        }
+
+        // the children predicted best among the rejected are evaluated after all
+        let mut screened = 0;
+        rejected.sort_by(|(_, a), (_, b)| direction.compare(*b, *a));
+        for (child, _) in rejected {
+            if accepted.len() < needed {
+                accepted.push(child);
+            } else {
+                screened += 1;
+            }
+        }
+        (accepted, screened)
+    }
+
+    // Rewards the operators of the evaluated children. A child identical to a
+    // member of the population, such as a mutation that changed nothing,
+    // earns nothing.
+    fn reward(&mut self, evaluated: &[Individual]) {
+        if self.state.hyperparameters.bandit.is_none() {
+            return;
+        }
+        let direction = self.configuration.direction;
+        let criterion = self.configuration.criterion;
+        let mut valid = self
+            .state
+            .population
+            .iter()
+            .filter_map(|individual| match individual.fitness {
+                Fitness::Valid(fitness) => Some(fitness),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        valid.sort_by(|a, b| direction.compare(*b, *a));
+        let median = valid.get(valid.len() / 2).copied();
+        let present = self
+            .state
+            .population
+            .iter()
+            .map(|individual| individual.id.clone())
+            .collect::<FxHashSet<_>>();
+        for (child, operator) in evaluated.iter().zip(&self.operators) {
+            let Some(operator) = operator else {
+                continue;
+            };
+            let statistics = &mut self.statistics[*operator as usize];
+            statistics.children += 1;
+            if !child.fitness.is_valid() || present.contains(&child.id) {
+                continue;
+            }
+            let fitness = child.fitness.into_f64(criterion);
+            if median.is_none_or(|median| direction.compare(fitness, median).is_gt()) {
+                statistics.successes += 1;
+            }
+            if direction.compare(fitness, self.best_overall).is_gt() {
+                statistics.improvements += 1;
+            }
+        }
+    }
+
+    // Moves the quality of each operator used in this generation towards its
+    // mean reward.
+    fn adapt(&mut self) {
+        let Some(bandit) = &self.state.hyperparameters.bandit else {
+            return;
+        };
+        let qualities = &mut self.state.qualities;
+        if qualities.len() != Operator::ALL.len() {
+            *qualities = vec![1.0; Operator::ALL.len()];
+        }
+        for (quality, statistics) in qualities.iter_mut().zip(&self.statistics) {
+            if statistics.children > 0 {
+                *quality += bandit.adaptation * (statistics.reward() - *quality);
+            }
+        }
+    }
+
+    // collects the valid children, replacing once enough are evaluated
+    fn gather(&mut self, evaluated: Vec<Individual>) {
+        if let Phase::Children { children, .. } = &mut self.phase {
+            children.extend(
+                evaluated
+                    .into_iter()
+                    .filter(|child| child.fitness.is_valid()),
+            );
+            if children.len() < self.state.hyperparameters.generate.value {
+                return;
+            }
+        }
+        if let Phase::Children {
+            holes, children, ..
+        } = std::mem::replace(&mut self.phase, Phase::Population)
+        {
+            self.adapt();
+            self.replace(holes, children);
+        }
+    }
+
+    // replace individuals with children
+    fn replace(&mut self, mut holes: Vec<usize>, mut children: Vec<Individual>) {
+        let state = &mut self.state;
+        let min = state
+            .hyperparameters
+            .generate
+            .value
+            .min(state.hyperparameters.delete.value);
+        let generated = children.split_off(min);
+        let mut deleted = holes.split_off(min);
+        assert!(generated.is_empty() || deleted.is_empty());
+        for (index, child) in children.into_iter().enumerate() {
+            state.population[holes[index]] = child;
+        }
+        if !generated.is_empty() {
+            for child in generated.into_iter() {
+                state.population.push(child);
+            }
+        }
+        if !deleted.is_empty() {
+            deleted.sort();
+            for index in deleted.into_iter().rev() {
+                // FIXME: strange behavior
+                state.population.remove(index);
+            }
+        }
+
+        for _ in 0..state.hyperparameters.infuse.value {
+            state
+                .population
+                .push(Individual::random(&self.configuration.profile));
+        }
+
+        state.step();
+    }
+}
+
+impl Strategy for Genetic<'_> {
+    fn ask(&mut self) -> Vec<Individual> {
+        let (children, screened) = match &self.phase {
+            Phase::Population => {
+                self.remove_duplicates();
+                return self.state.population.clone();
+            }
+            Phase::Children {
+                crowded,
+                flattened,
+                children,
+                ..
+            } => {
+                let state = &self.state;
+                let profile = &self.configuration.profile;
+                let probabilities = state
+                    .hyperparameters
+                    .bandit
+                    .as_ref()
+                    .map(|bandit| Operator::probabilities(&state.qualities, bandit.minimum));
+                let parents = |n| {
+                    if let Some((ranks, distances)) = crowded {
+                        genetic::crowded_tournament(ranks, distances, n)
+                    } else {
+                        genetic::select(&state.hyperparameters.selection, flattened, n, false)
+                    }
+                };
+                let breed = |n: usize| -> Vec<(Individual, Option<Operator>)> {
+                    (0..n)
+                        .into_par_iter()
+                        .map(|_| {
+                            let operator = probabilities.as_deref().map(Operator::draw);
+                            let mutation = match (operator, &state.hyperparameters.bandit) {
+                                (Some(Operator::Large), Some(bandit)) => &bandit.large,
+                                _ => &state.hyperparameters.mutate,
+                            };
+                            let child = match operator {
+                                Some(Operator::Immigrant) => Individual::random(profile),
+                                Some(Operator::Mutation | Operator::Large) => {
+                                    let parent = &state.population[parents(1)[0]];
+                                    let mut child = Individual::new(parent.parameters.clone());
+                                    genetic::mutate(profile, mutation, &mut child);
+                                    child
+                                }
+                                Some(Operator::Crossover) | None => {
+                                    let result = parents(2);
+                                    let mut child = genetic::crossover(
+                                        profile,
+                                        &state.hyperparameters.crossover,
+                                        &state.population[result[0]],
+                                        &state.population[result[1]],
+                                    );
+                                    if operator.is_none() {
+                                        genetic::mutate(profile, mutation, &mut child);
+                                    } else {
+                                        // mutation adjusts the child, crossover alone does not
+                                        profile.adjust(&mut child);
+                                    }
+                                    child
+                                }
+                            };
+                            (child, operator)
+                        })
+                        .collect()
+                };
+                let needed = state.hyperparameters.generate.value - children.len();
+                match &state.hyperparameters.surrogate {
+                    Some(surrogate) if state.archive.len() >= surrogate.warmup => {
+                        self.screen(surrogate, needed, breed)
+                    }
+                    _ => (breed(needed), 0),
+                }
+            }
+            Phase::Finished => return Vec::new(),
+        };
+        self.screened += screened;
+        let (children, operators) = children.into_iter().unzip();
+        self.operators = operators;
+        children
+    }
+
+    fn tell(&mut self, evaluated: Vec<Individual>, progress: &mut Progress) {
+        if self.state.hyperparameters.surrogate.is_some() {
+            let criterion = self.configuration.criterion;
+            for individual in &evaluated {
+                if individual.fitness.is_valid() {
+                    let fitness = individual.fitness.into_f64(criterion);
+                    self.state.archive.insert(
+                        individual.id.clone(),
+                        (individual.parameters.clone(), fitness),
+                    );
+                }
+            }
+        }
+
+        match &mut self.phase {
+            Phase::Population => {
+                self.state.population = evaluated;
+                self.phase = self.conclude(progress);
+            }
+            Phase::Children { .. } => {
+                self.reward(&evaluated);
+                self.gather(evaluated);
+            }
+            Phase::Finished => {}
+        }
+    }
+
+    fn checkpoint(&self) -> serde_json::Value {
+        serde_json::to_value(&self.state).unwrap()
+    }
+}
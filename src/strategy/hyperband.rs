@@ -0,0 +1,252 @@
+use crate::{
+    configuration::{Configuration, Hyperband as Options},
+    individual::Individual,
+    strategy::{self, Progress, Strategy},
+};
+use anyhow::anyhow;
+use fxhash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Default)]
+struct State {
+    iteration: usize,
+    bracket: usize,
+    rung: usize,
+    // promoted to the current rung, empty at the first one
+    survivors: Vec<Individual>,
+}
+
+pub(crate) struct Hyperband<'a> {
+    configuration: &'a Configuration,
+    options: &'a Options,
+    state: State,
+    // number of rungs after the first of the most exploratory bracket
+    depth: usize,
+    // the number of rungs after the first of each bracket, in order
+    brackets: Vec<usize>,
+}
+
+impl<'a> Hyperband<'a> {
+    pub(crate) fn new(
+        configuration: &'a Configuration,
+        options: &'a Options,
+        checkpoint: Option<serde_json::Value>,
+    ) -> anyhow::Result<Self> {
+        if options.minimum <= 0.0 || options.minimum > 1.0 {
+            return Err(anyhow!("Minimum fidelity must be in (0, 1]"));
+        }
+        if options.eta < 2 {
+            return Err(anyhow!("Eta must be at least 2"));
+        }
+        if options.iterations == 0 {
+            return Err(anyhow!("Number of iterations must be greater than 0"));
+        }
+
+        let eta = options.eta as f64;
+        let mut depth = 0;
+        while eta.powi(depth as i32 + 1) * options.minimum <= 1.0 + f64::EPSILON {
+            depth += 1;
+        }
+        let brackets = if options.halving {
+            vec![depth]
+        } else {
+            (0..=depth).rev().collect()
+        };
+
+        let state = if let Some(checkpoint) = checkpoint {
+            serde_json::from_value(checkpoint)?
+        } else {
+            State::default()
+        };
+
+        Ok(Hyperband {
+            configuration,
+            options,
+            state,
+            depth,
+            brackets,
+        })
+    }
+
+    fn is_finished(&self) -> bool {
+        self.state.iteration >= self.options.iterations
+    }
+
+    // number of candidates at the given rung of the bracket with `s` rungs
+    // after the first
+    fn size(&self, s: usize, rung: usize) -> usize {
+        let eta = self.options.eta as f64;
+        let initial = ((self.depth + 1) as f64 * eta.powi(s as i32) / (s + 1) as f64).ceil();
+        ((initial / eta.powi(rung as i32)).floor() as usize).max(1)
+    }
+
+    fn fidelity(&self, s: usize, rung: usize) -> f64 {
+        (self.options.eta as f64).powi(rung as i32 - s as i32)
+    }
+
+    fn advance(&mut self) {
+        self.state.rung = 0;
+        self.state.survivors.clear();
+        self.state.bracket += 1;
+        if self.state.bracket == self.brackets.len() {
+            self.state.bracket = 0;
+            self.state.iteration += 1;
+        }
+    }
+}
+
+impl Strategy for Hyperband<'_> {
+    fn ask(&mut self) -> Vec<Individual> {
+        if self.is_finished() {
+            return Vec::new();
+        }
+        let s = self.brackets[self.state.bracket];
+        let fidelity = self.fidelity(s, self.state.rung);
+
+        let mut batch = if self.state.rung == 0 {
+            let profile = &self.configuration.profile;
+            let mut seen = FxHashSet::default();
+            let (batch, _) =
+                strategy::draw_unseen(self.size(s, 0), &mut seen, |_| Individual::random(profile));
+            batch
+        } else {
+            self.state
+                .survivors
+                .iter()
+                .map(|survivor| Individual::new(survivor.parameters.clone()))
+                .collect()
+        };
+        for individual in &mut batch {
+            individual.fidelity = fidelity;
+        }
+        batch
+    }
+
+    fn tell(&mut self, evaluated: Vec<Individual>, progress: &mut Progress) {
+        let direction = self.configuration.direction;
+        let criterion = self.configuration.criterion;
+        let s = self.brackets[self.state.bracket];
+        let fidelity = self.fidelity(s, self.state.rung);
+
+        // fitness is only comparable among candidates at the same fidelity
+        let mut valid = evaluated
+            .into_iter()
+            .filter(|individual| individual.fitness.is_valid())
+            .collect::<Vec<_>>();
+        valid.sort_by(|a, b| {
+            direction.compare(b.fitness.into_f64(criterion), a.fitness.into_f64(criterion))
+        });
+        let boundaries = strategy::boundaries(self.configuration, &valid);
+        if let Some(summary) = progress.summary_at(fidelity, boundaries) {
+            let bracket = self.state.iteration * self.brackets.len() + self.state.bracket + 1;
+            progress.summarize(
+                format!("Bracket #{} Rung #{}", bracket, self.state.rung + 1),
+                summary,
+            );
+        }
+
+        if self.state.rung < s && !valid.is_empty() {
+            valid.truncate(self.size(s, self.state.rung + 1));
+            self.state.survivors = valid;
+            self.state.rung += 1;
+        } else {
+            self.advance();
+        }
+        writeln!(progress.log).unwrap();
+    }
+
+    fn checkpoint(&self) -> serde_json::Value {
+        serde_json::to_value(&self.state).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        configuration,
+        individual::Fitness,
+        output::{Output, Ranking},
+    };
+
+    fn parse(halving: bool) -> Configuration {
+        serde_json::from_str(&format!(
+            r#"{{"direction": "minimize", "criterion": "median",
+                "profile": {{"x": {{"Integer": {{"space": {{"Sequence": [0, 1000000]}}}}}}}},
+                "helper": {{"pre": "pre", "post": "post"}}, "runner": "run", "compiler": "cc",
+                "strategy": {{"Hyperband": {{"minimum": {}, "halving": {}}}}}}}"#,
+            1.0 / 9.0,
+            halving
+        ))
+        .unwrap()
+    }
+
+    fn options(configuration: &Configuration) -> &Options {
+        match &configuration.strategy {
+            configuration::Strategy::Hyperband(options) => options,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_brackets() {
+        let configuration = parse(false);
+        let hyperband = Hyperband::new(&configuration, options(&configuration), None).unwrap();
+        assert_eq!(hyperband.depth, 2);
+        assert_eq!(hyperband.brackets, vec![2, 1, 0]);
+        let sizes = (0..=2).map(|rung| hyperband.size(2, rung));
+        assert_eq!(sizes.collect::<Vec<_>>(), vec![9, 3, 1]);
+        let fidelities = (0..=2).map(|rung| hyperband.fidelity(2, rung));
+        for (fidelity, expected) in fidelities.zip([1.0 / 9.0, 1.0 / 3.0, 1.0]) {
+            assert!((fidelity - expected).abs() < 1e-9);
+        }
+        assert_eq!(hyperband.size(0, 0), 3);
+        assert_eq!(hyperband.fidelity(0, 0), 1.0);
+
+        let configuration = parse(true);
+        let hyperband = Hyperband::new(&configuration, options(&configuration), None).unwrap();
+        assert_eq!(hyperband.brackets, vec![2]);
+    }
+
+    #[test]
+    fn test_promotion() {
+        let configuration = parse(true);
+        let mut hyperband = Hyperband::new(&configuration, options(&configuration), None).unwrap();
+        let mut output = Output::new(configuration.direction, &[], 100);
+        let mut log = Vec::new();
+        let unit = None;
+
+        // the best third of each rung is promoted, invalid ones never
+        let ids = |batch: &[Individual]| batch.iter().map(|i| i.id.clone()).collect::<Vec<_>>();
+        let mut rungs = Vec::new();
+        for size in [9, 3, 1] {
+            let mut batch = hyperband.ask();
+            assert_eq!(batch.len(), size);
+            for (i, individual) in batch.iter_mut().enumerate() {
+                individual.fitness = if i == 0 && size > 1 {
+                    Fitness::Invalid
+                } else {
+                    Fitness::Valid((size - i) as f64)
+                };
+                output.push(individual);
+            }
+            rungs.push(ids(&batch));
+            let mut progress = Progress::new(&mut output, &mut log, &unit);
+            hyperband.tell(batch, &mut progress);
+        }
+        let promoted = rungs[0][6..].iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(rungs[1], promoted);
+        assert_eq!(rungs[2], rungs[1][2..]);
+        assert_eq!(hyperband.state.iteration, 1);
+        assert!(hyperband.ask().is_empty());
+
+        // each fidelity is ranked on its own
+        let fidelities = output.fidelities.iter().map(|(fidelity, _)| *fidelity);
+        let expected = vec![hyperband.fidelity(2, 0), hyperband.fidelity(2, 1)];
+        assert_eq!(fidelities.collect::<Vec<_>>(), expected);
+        let best = |ranking: &Ranking| ranking.best().unwrap().id.clone();
+        assert_eq!(best(&output.fidelities[0].1), rungs[0][8]);
+        assert_eq!(best(&output.fidelities[1].1), rungs[1][2]);
+        assert_eq!(best(&output.ranking), rungs[2][0]);
+    }
+}
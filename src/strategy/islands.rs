@@ -0,0 +1,179 @@
+use crate::{
+    configuration::{Configuration, Islands as Options, Topology},
+    individual::Individual,
+    strategy::{Progress, Strategy, genetic::Genetic},
+};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    islands: Vec<serde_json::Value>,
+}
+
+// Independent genetic algorithms advancing in lockstep, one generation at a
+// time, and exchanging their elites every `interval` generations.
+pub(crate) struct Islands<'a> {
+    options: &'a Options,
+    islands: Vec<Genetic<'a>>,
+    // generation every island has to complete before any proceeds
+    barrier: usize,
+    // number of individuals proposed by each island in the latest batch
+    sizes: Vec<usize>,
+}
+
+impl<'a> Islands<'a> {
+    pub(crate) fn new(
+        configuration: &'a Configuration,
+        options: &'a Options,
+        checkpoint: Option<serde_json::Value>,
+    ) -> anyhow::Result<Self> {
+        if options.count < 2 {
+            return Err(anyhow!("Number of islands must be greater than 1"));
+        }
+        if !options.hyperparameters.is_empty() && options.hyperparameters.len() != options.count {
+            return Err(anyhow!(
+                "Hyperparameters must be given for every island or for none"
+            ));
+        }
+        if options.interval == 0 {
+            return Err(anyhow!("Migration interval must be greater than 0"));
+        }
+
+        let mut checkpoints = match checkpoint {
+            Some(checkpoint) => {
+                let state = serde_json::from_value::<State>(checkpoint)?;
+                if state.islands.len() != options.count {
+                    return Err(anyhow!(
+                        "Checkpoint has {} islands, but {} are configured",
+                        state.islands.len(),
+                        options.count
+                    ));
+                }
+                state.islands.into_iter().map(Some).collect()
+            }
+            None => vec![None; options.count],
+        };
+        let islands = (0..options.count)
+            .map(|i| {
+                let hyperparameters = options
+                    .hyperparameters
+                    .get(i)
+                    .unwrap_or(&configuration.hyperparameters);
+                Genetic::with(
+                    configuration,
+                    hyperparameters,
+                    format!("Island #{} ", i + 1),
+                    checkpoints[i].take(),
+                )
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let barrier = islands.iter().map(Genetic::generation).min().unwrap();
+
+        Ok(Islands {
+            options,
+            islands,
+            barrier,
+            sizes: Vec::new(),
+        })
+    }
+
+    // whether the island has completed the current generation
+    fn is_waiting(&self, island: &Genetic) -> bool {
+        island.is_finished() || (island.is_settled() && island.generation() > self.barrier)
+    }
+
+    fn migrate(&mut self) {
+        let count = self.islands.len();
+        let emigrants = self
+            .islands
+            .iter()
+            .map(|island| {
+                if island.is_finished() {
+                    Vec::new()
+                } else {
+                    island.elite(self.options.migrants)
+                }
+            })
+            .collect::<Vec<_>>();
+        for (i, island) in self.islands.iter_mut().enumerate() {
+            if island.is_finished() {
+                continue;
+            }
+            let immigrants = match self.options.topology {
+                Topology::Ring => emigrants[(i + count - 1) % count].clone(),
+                Topology::Full => emigrants
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .flat_map(|(_, emigrants)| emigrants.iter().cloned())
+                    .collect(),
+            };
+            island.welcome(immigrants);
+        }
+    }
+}
+
+// Whether the elites migrate once `completed` generations are done, i.e.
+// after generation K, 2K, ... for an interval of K. The barrier is the
+// generation in progress, so one less have been completed.
+fn migrates(completed: usize, interval: usize) -> bool {
+    completed > 0 && completed.is_multiple_of(interval)
+}
+
+impl Strategy for Islands<'_> {
+    fn ask(&mut self) -> Vec<Individual> {
+        if self.islands.iter().all(|island| self.is_waiting(island)) {
+            if self.islands.iter().all(Genetic::is_finished) {
+                return Vec::new();
+            }
+            self.barrier += 1;
+            if migrates(self.barrier - 1, self.options.interval) {
+                self.migrate();
+            }
+        }
+
+        let mut batch = Vec::new();
+        self.sizes = vec![0; self.islands.len()];
+        for i in 0..self.islands.len() {
+            if self.is_waiting(&self.islands[i]) {
+                continue;
+            }
+            let proposed = self.islands[i].ask();
+            self.sizes[i] = proposed.len();
+            batch.extend(proposed);
+        }
+        batch
+    }
+
+    fn tell(&mut self, evaluated: Vec<Individual>, progress: &mut Progress) {
+        let mut evaluated = evaluated.into_iter();
+        for (island, &size) in self.islands.iter_mut().zip(&self.sizes) {
+            if size > 0 {
+                island.tell(evaluated.by_ref().take(size).collect(), progress);
+            }
+        }
+    }
+
+    fn checkpoint(&self) -> serde_json::Value {
+        serde_json::to_value(State {
+            islands: self.islands.iter().map(Genetic::checkpoint).collect(),
+        })
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_schedule() {
+        let schedule = (0..=7).map(|completed| migrates(completed, 3));
+        assert_eq!(
+            schedule.collect::<Vec<_>>(),
+            vec![false, false, false, true, false, false, true, false]
+        );
+        assert!((1..=4).all(|completed| migrates(completed, 1)));
+    }
+}
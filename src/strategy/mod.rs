@@ -0,0 +1,198 @@
+mod annealing;
+mod differential;
+mod exhaustive;
+mod genetic;
+mod hyperband;
+mod islands;
+mod random;
+mod tpe;
+
+use crate::{
+    configuration::{self, Configuration, Termination},
+    direction::Direction,
+    genetic::GenerationSummary,
+    individual::Individual,
+    output::{Output, Ranking},
+};
+use fxhash::FxHashSet;
+use std::{fmt::Display, io, sync::Arc};
+
+// give up on unseen candidates after this many consecutive duplicates
+const ATTEMPTS: usize = 1000;
+
+// A search strategy proposes candidates (`ask`) and learns from their
+// evaluation (`tell`). Compilation, evaluation, the ranking and logging are
+// handled by the caller.
+pub(crate) trait Strategy {
+    // An empty batch ends the search.
+    fn ask(&mut self) -> Vec<Individual>;
+    // Receives the latest batch in the order it was proposed.
+    fn tell(&mut self, evaluated: Vec<Individual>, progress: &mut Progress);
+    fn checkpoint(&self) -> serde_json::Value;
+}
+
+pub(crate) struct Progress<'a> {
+    pub(crate) ranking: &'a Ranking,
+    history: &'a mut Vec<GenerationSummary>,
+    fidelities: &'a [(f64, Ranking)],
+    pub(crate) log: &'a mut dyn io::Write,
+    unit: &'a Option<String>,
+}
+
+impl<'a> Progress<'a> {
+    pub(crate) fn new(
+        output: &'a mut Output,
+        log: &'a mut dyn io::Write,
+        unit: &'a Option<String>,
+    ) -> Self {
+        let Output {
+            ranking,
+            history,
+            fidelities,
+        } = output;
+        Progress {
+            ranking,
+            history,
+            fidelities,
+            log,
+            unit,
+        }
+    }
+
+    // the ranking of the individuals evaluated at `fidelity`
+    pub(crate) fn ranking_at(&self, fidelity: f64) -> Option<&Ranking> {
+        if fidelity == 1.0 {
+            return Some(self.ranking);
+        }
+        self.fidelities
+            .iter()
+            .find(|(other, _)| *other == fidelity)
+            .map(|(_, ranking)| ranking)
+    }
+
+    // Summary of a batch whose valid fitness spans `boundaries`, None if the
+    // batch or the ranking has no valid individual yet.
+    pub(crate) fn summary(&self, boundaries: (f64, f64)) -> Option<GenerationSummary> {
+        self.summary_at(1.0, boundaries)
+    }
+
+    pub(crate) fn summary_at(
+        &self,
+        fidelity: f64,
+        boundaries: (f64, f64),
+    ) -> Option<GenerationSummary> {
+        let global = self.ranking_at(fidelity)?.best()?;
+        if !boundaries.0.is_finite() {
+            return None;
+        }
+        let mut summary = GenerationSummary::new(global, boundaries);
+        if fidelity != 1.0 {
+            summary.fidelity = Some(fidelity);
+        }
+        Some(summary)
+    }
+
+    pub(crate) fn summarize(&mut self, title: impl Display, summary: GenerationSummary) {
+        writeln!(self.log, "=== {} Summary ===", title).unwrap();
+        summary.print(self.log, self.unit).unwrap();
+        self.history.push(summary);
+    }
+
+    // Whether to stop after the `generation`-th generation, the best fitness
+    // `best` not having improved for the last `count` of them. A reached goal
+    // is cleared so that `endure` applies from then on.
+    pub(crate) fn terminate(
+        &mut self,
+        terminate: &mut Termination,
+        direction: Direction,
+        generation: usize,
+        count: usize,
+        best: Option<f64>,
+    ) -> bool {
+        if terminate.limit.is_some_and(|limit| generation >= limit) {
+            return true;
+        }
+        if let Some(goal) = terminate.goal {
+            if best.is_some_and(|best| direction.compare(best, goal).is_ge()) {
+                terminate.goal = None;
+            }
+        } else if let Some(endure) = terminate.endure {
+            writeln!(self.log, "{}/{}", count, endure).unwrap();
+            return count >= endure;
+        }
+        false
+    }
+}
+
+// Best and worst valid fitness of `individuals`, infinite if none is valid.
+pub(crate) fn boundaries<'b>(
+    configuration: &Configuration,
+    individuals: impl IntoIterator<Item = &'b Individual>,
+) -> (f64, f64) {
+    let criterion = configuration.criterion;
+    configuration.direction.boundaries(
+        individuals
+            .into_iter()
+            .map(|individual| individual.fitness.into_f64(criterion))
+            .filter(|x| x.is_finite()),
+    )
+}
+
+// Draws up to `size` candidates not in `seen` yet and adds them to it. Also
+// returns whether the draws were given up on, only duplicates coming out.
+pub(crate) fn draw_unseen(
+    size: usize,
+    seen: &mut FxHashSet<Arc<str>>,
+    mut draw: impl FnMut(&FxHashSet<Arc<str>>) -> Individual,
+) -> (Vec<Individual>, bool) {
+    let mut batch = Vec::with_capacity(size);
+    let mut attempts = 0;
+    while batch.len() < size && attempts < ATTEMPTS {
+        let candidate = draw(seen);
+        if seen.insert(candidate.id.clone()) {
+            batch.push(candidate);
+            attempts = 0;
+        } else {
+            attempts += 1;
+        }
+    }
+    (batch, attempts == ATTEMPTS)
+}
+
+pub(crate) fn new<'a>(
+    configuration: &'a Configuration,
+    checkpoint: Option<serde_json::Value>,
+) -> anyhow::Result<Box<dyn Strategy + 'a>> {
+    Ok(match configuration.strategy {
+        configuration::Strategy::Genetic => {
+            Box::new(genetic::Genetic::new(configuration, checkpoint)?)
+        }
+        configuration::Strategy::Annealing(ref options) => Box::new(annealing::Annealing::new(
+            configuration,
+            options,
+            checkpoint,
+        )?),
+        configuration::Strategy::Tpe(ref options) => {
+            Box::new(tpe::Tpe::new(configuration, options, checkpoint)?)
+        }
+        configuration::Strategy::Exhaustive(ref options) => Box::new(exhaustive::Exhaustive::new(
+            configuration,
+            options,
+            checkpoint,
+        )?),
+        configuration::Strategy::Islands(ref options) => {
+            Box::new(islands::Islands::new(configuration, options, checkpoint)?)
+        }
+        configuration::Strategy::Hyperband(ref options) => Box::new(hyperband::Hyperband::new(
+            configuration,
+            options,
+            checkpoint,
+        )?),
+        configuration::Strategy::Differential(ref options) => Box::new(
+            differential::Differential::new(configuration, options, checkpoint)?,
+        ),
+        configuration::Strategy::Random(ref options) => {
+            Box::new(random::Random::new(configuration, options, checkpoint)?)
+        }
+    })
+}
@@ -0,0 +1,95 @@
+use crate::{
+    configuration::{Configuration, RandomSearch},
+    individual::Individual,
+    strategy::{self, Progress, Strategy},
+};
+use anyhow::anyhow;
+use fxhash::FxHashSet;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Instant};
+
+#[derive(Serialize, Deserialize, Default)]
+struct State {
+    batch: usize,
+    evaluated: usize,
+    // seconds spent before the run was resumed
+    elapsed: f64,
+    seen: FxHashSet<Arc<str>>,
+}
+
+pub(crate) struct Random<'a> {
+    configuration: &'a Configuration,
+    options: &'a RandomSearch,
+    state: State,
+    start: Instant,
+    exhausted: bool,
+}
+
+impl<'a> Random<'a> {
+    pub(crate) fn new(
+        configuration: &'a Configuration,
+        options: &'a RandomSearch,
+        checkpoint: Option<serde_json::Value>,
+    ) -> anyhow::Result<Self> {
+        if options.batch == 0 {
+            return Err(anyhow!("Batch size must be greater than 0"));
+        }
+
+        let state = if let Some(checkpoint) = checkpoint {
+            serde_json::from_value(checkpoint)?
+        } else {
+            State::default()
+        };
+
+        Ok(Random {
+            configuration,
+            options,
+            state,
+            start: Instant::now(),
+            exhausted: false,
+        })
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.state.elapsed + self.start.elapsed().as_secs_f64()
+    }
+}
+
+impl Strategy for Random<'_> {
+    fn ask(&mut self) -> Vec<Individual> {
+        if self.exhausted {
+            return Vec::new();
+        }
+        let size =
+            self.options
+                .budget
+                .remaining(self.options.batch, self.state.evaluated, self.elapsed());
+
+        let profile = &self.configuration.profile;
+        let (batch, exhausted) =
+            strategy::draw_unseen(size, &mut self.state.seen, |_| Individual::random(profile));
+        self.exhausted = exhausted;
+        batch
+    }
+
+    fn tell(&mut self, evaluated: Vec<Individual>, progress: &mut Progress) {
+        self.state.batch += 1;
+        self.state.evaluated += evaluated.len();
+
+        let boundaries = strategy::boundaries(self.configuration, &evaluated);
+        if let Some(summary) = progress.summary(boundaries) {
+            progress.summarize(format!("Batch #{}", self.state.batch), summary);
+        }
+        let budget = self
+            .options
+            .budget
+            .progress(self.state.evaluated, self.elapsed());
+        writeln!(progress.log, "{}\n", budget).unwrap();
+    }
+
+    fn checkpoint(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(&self.state).unwrap();
+        value["elapsed"] = self.elapsed().into();
+        value
+    }
+}
@@ -0,0 +1,373 @@
+use crate::{
+    configuration::{Configuration, Tpe as Options},
+    individual::{Fitness, Individual},
+    parameter::{Specification, Value, space},
+    strategy::{self, Progress, Strategy},
+};
+use anyhow::anyhow;
+use fxhash::FxHashSet;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, f64::consts::PI, sync::Arc, time::Instant};
+
+#[derive(Serialize, Deserialize)]
+struct Observation {
+    parameters: Individual,
+    // None for invalid individuals
+    fitness: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct State {
+    batch: usize,
+    // seconds spent before the run was resumed
+    elapsed: f64,
+    observations: Vec<Observation>,
+}
+
+// Parzen estimator of a single parameter, with a uniform prior mixed in.
+enum Density {
+    // kernels over the integer range mapped onto [0, 1]
+    Continuous {
+        start: u32,
+        end: u32,
+        points: Vec<f64>,
+        bandwidth: f64,
+    },
+    Categorical(Vec<f64>),
+}
+
+impl Density {
+    fn new(specification: &Specification, values: &[Value]) -> Self {
+        match specification {
+            Specification::Integer {
+                space: space::Integer::Sequence(start, end),
+                ..
+            } if start < end => {
+                let range = (end - start) as f64;
+                let points = values
+                    .iter()
+                    .map(|value| match value {
+                        Value::Integer(x) => (*x as f64 - *start as f64) / range,
+                        _ => unreachable!(),
+                    })
+                    .collect::<Vec<_>>();
+                let n = points.len().max(1) as f64;
+                let mean = points.iter().sum::<f64>() / n;
+                let deviation = (points.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt();
+                // Scott's rule, but never narrower than a single step
+                let bandwidth = (1.06 * deviation * n.powf(-0.2)).max(1.0 / (range + 1.0));
+                Density::Continuous {
+                    start: *start,
+                    end: *end,
+                    points,
+                    bandwidth,
+                }
+            }
+            _ => {
+                let levels = match specification {
+                    Specification::Integer {
+                        space: space::Integer::Candidates(candidates),
+                        ..
+                    } => candidates.len(),
+                    Specification::Keyword(space::Keyword(options)) => options.len(),
+                    Specification::Switch => 2,
+                    // single-valued sequence
+                    Specification::Integer { .. } => 1,
+                };
+                let mut counts = vec![1.0; levels];
+                for value in values {
+                    counts[Self::level(*value)] += 1.0;
+                }
+                let total = counts.iter().sum::<f64>();
+                Density::Categorical(counts.into_iter().map(|count| count / total).collect())
+            }
+        }
+    }
+
+    fn level(value: Value) -> usize {
+        match value {
+            Value::Index(i) => i,
+            Value::Switch(b) => b as usize,
+            Value::Integer(_) => 0,
+        }
+    }
+
+    fn pdf(&self, value: Value) -> f64 {
+        match (self, value) {
+            (
+                Density::Continuous {
+                    start,
+                    end,
+                    points,
+                    bandwidth,
+                },
+                Value::Integer(x),
+            ) => {
+                let x = (x as f64 - *start as f64) / (end - start) as f64;
+                let kernels = points
+                    .iter()
+                    .map(|point| {
+                        let z = (x - point) / bandwidth;
+                        (-0.5 * z * z).exp() / (bandwidth * (2.0 * PI).sqrt())
+                    })
+                    .sum::<f64>();
+                (kernels + 1.0) / (points.len() + 1) as f64
+            }
+            (Density::Categorical(probabilities), value) => probabilities[Self::level(value)],
+            _ => unreachable!(),
+        }
+    }
+
+    fn sample(&self, specification: &Specification, rng: &mut impl Rng) -> Value {
+        match self {
+            Density::Continuous {
+                start,
+                end,
+                points,
+                bandwidth,
+            } => {
+                let index = rng.random_range(0..=points.len());
+                let x = if index == points.len() {
+                    rng.random::<f64>()
+                } else {
+                    // Box-Muller transform
+                    let (u, v) = (1.0 - rng.random::<f64>(), rng.random::<f64>());
+                    let z = (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos();
+                    (points[index] + z * bandwidth).clamp(0.0, 1.0)
+                };
+                Value::Integer(*start + (x * (end - start) as f64).round() as u32)
+            }
+            Density::Categorical(probabilities) => {
+                let mut pointer = rng.random::<f64>();
+                let mut level = probabilities.len() - 1;
+                for (i, probability) in probabilities.iter().enumerate() {
+                    if pointer < *probability {
+                        level = i;
+                        break;
+                    }
+                    pointer -= probability;
+                }
+                match specification {
+                    Specification::Switch => Value::Switch(level == 1),
+                    Specification::Integer {
+                        space: space::Integer::Sequence(start, _),
+                        ..
+                    } => Value::Integer(*start),
+                    _ => Value::Index(level),
+                }
+            }
+        }
+    }
+}
+
+pub(crate) struct Tpe<'a> {
+    configuration: &'a Configuration,
+    options: &'a Options,
+    state: State,
+    start: Instant,
+    exhausted: bool,
+}
+
+impl<'a> Tpe<'a> {
+    pub(crate) fn new(
+        configuration: &'a Configuration,
+        options: &'a Options,
+        checkpoint: Option<serde_json::Value>,
+    ) -> anyhow::Result<Self> {
+        if options.batch == 0 || options.samples == 0 {
+            return Err(anyhow!(
+                "Batch size and number of samples must be greater than 0"
+            ));
+        }
+        if options.gamma <= 0.0 || options.gamma >= 1.0 {
+            return Err(anyhow!("Gamma must be between 0 and 1"));
+        }
+
+        let state = if let Some(checkpoint) = checkpoint {
+            serde_json::from_value(checkpoint)?
+        } else {
+            State::default()
+        };
+
+        Ok(Tpe {
+            configuration,
+            options,
+            state,
+            start: Instant::now(),
+            exhausted: false,
+        })
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.state.elapsed + self.start.elapsed().as_secs_f64()
+    }
+
+    // Splits the observations into the best `gamma` quantile and the rest,
+    // and fits a pair of densities for every parameter.
+    fn fit(&self) -> Option<BTreeMap<Arc<str>, (Density, Density)>> {
+        let mut valid = self
+            .state
+            .observations
+            .iter()
+            .filter_map(|observation| Some((observation.fitness?, &observation.parameters)))
+            .collect::<Vec<_>>();
+        if self.state.observations.len() < self.options.startup || valid.len() < 2 {
+            return None;
+        }
+        let direction = self.configuration.direction;
+        valid.sort_by(|(a, _), (b, _)| direction.compare(*b, *a));
+        let good = ((self.options.gamma * valid.len() as f64).ceil() as usize).max(1);
+        let bad = valid[good..]
+            .iter()
+            .map(|(_, individual)| *individual)
+            .chain(
+                self.state
+                    .observations
+                    .iter()
+                    .filter(|observation| observation.fitness.is_none())
+                    .map(|observation| &observation.parameters),
+            )
+            .collect::<Vec<_>>();
+        let good = valid[..good]
+            .iter()
+            .map(|(_, individual)| *individual)
+            .collect::<Vec<_>>();
+
+        Some(
+            self.configuration
+                .profile
+                .0
+                .iter()
+                .map(|(name, specification)| {
+                    let values = |individuals: &[&Individual]| {
+                        individuals
+                            .iter()
+                            .map(|individual| individual.parameters[name])
+                            .collect::<Vec<_>>()
+                    };
+                    let l = Density::new(specification, &values(&good));
+                    let g = Density::new(specification, &values(&bad));
+                    (name.clone(), (l, g))
+                })
+                .collect(),
+        )
+    }
+
+    // Draws candidates from the good densities and picks the one maximizing
+    // l(x) / g(x), evaluated after the conditions have been applied.
+    fn propose(
+        &self,
+        densities: &BTreeMap<Arc<str>, (Density, Density)>,
+        seen: &FxHashSet<Arc<str>>,
+    ) -> Option<Individual> {
+        let profile = &self.configuration.profile;
+        let mut rng = rand::rng();
+        (0..self.options.samples)
+            .map(|_| {
+                let mut candidate = Individual::new(
+                    densities
+                        .iter()
+                        .map(|(name, (l, _))| (name.clone(), l.sample(&profile.0[name], &mut rng)))
+                        .collect(),
+                );
+                profile.adjust(&mut candidate);
+                let score = densities
+                    .iter()
+                    .map(|(name, (l, g))| {
+                        let value = candidate.parameters[name];
+                        l.pdf(value).ln() - g.pdf(value).ln()
+                    })
+                    .sum::<f64>();
+                (candidate, score)
+            })
+            .filter(|(candidate, _)| !seen.contains(&candidate.id))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(candidate, _)| candidate)
+    }
+}
+
+impl Strategy for Tpe<'_> {
+    fn ask(&mut self) -> Vec<Individual> {
+        if self.exhausted {
+            return Vec::new();
+        }
+        let size = self.options.budget.remaining(
+            self.options.batch,
+            self.state.observations.len(),
+            self.elapsed(),
+        );
+
+        let mut seen = self
+            .state
+            .observations
+            .iter()
+            .map(|observation| observation.parameters.id.clone())
+            .collect::<FxHashSet<_>>();
+        let densities = self.fit();
+        let (batch, exhausted) = strategy::draw_unseen(size, &mut seen, |seen| {
+            densities
+                .as_ref()
+                .and_then(|densities| self.propose(densities, seen))
+                .unwrap_or_else(|| Individual::random(&self.configuration.profile))
+        });
+        self.exhausted = exhausted;
+        batch
+    }
+
+    fn tell(&mut self, evaluated: Vec<Individual>, progress: &mut Progress) {
+        self.state.batch += 1;
+
+        let boundaries = strategy::boundaries(self.configuration, &evaluated);
+        if let Some(summary) = progress.summary(boundaries) {
+            progress.summarize(format!("Batch #{}", self.state.batch), summary);
+        }
+
+        self.state
+            .observations
+            .extend(evaluated.into_iter().map(|individual| Observation {
+                fitness: match individual.fitness {
+                    Fitness::Valid(x) => Some(x),
+                    _ => None,
+                },
+                parameters: individual,
+            }));
+
+        let budget = self
+            .options
+            .budget
+            .progress(self.state.observations.len(), self.elapsed());
+        writeln!(progress.log, "{}\n", budget).unwrap();
+    }
+
+    fn checkpoint(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(&self.state).unwrap();
+        value["elapsed"] = self.elapsed().into();
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_density() {
+        let specification = Specification::Integer {
+            space: space::Integer::Sequence(0, 100),
+            condition: None,
+        };
+        let density = Density::new(&specification, &[Value::Integer(10), Value::Integer(12)]);
+        assert!(density.pdf(Value::Integer(11)) > density.pdf(Value::Integer(90)));
+
+        let density = Density::new(&Specification::Switch, &[Value::Switch(true)]);
+        assert!(density.pdf(Value::Switch(true)) > density.pdf(Value::Switch(false)));
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            assert!(matches!(
+                density.sample(&Specification::Switch, &mut rng),
+                Value::Switch(_)
+            ));
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use sha2::{Digest, Sha256};
+use std::{ffi::OsStr, fs, path::Path};
+
+// Hashes every part in order. Parts naming an existing file (sources,
+// generated headers, ...) contribute their contents as well, so an edited
+// file changes the digest even if its path does not.
+pub(crate) fn digest<S: AsRef<OsStr>>(parts: impl Iterator<Item = S>) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        let part = part.as_ref();
+        update(&mut hasher, part.as_encoded_bytes());
+        let path = Path::new(part);
+        if path.is_file()
+            && let Ok(content) = fs::read(path)
+        {
+            update(&mut hasher, &content);
+        }
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn update(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
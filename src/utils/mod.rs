@@ -1,3 +1,4 @@
+pub(crate) mod digest;
 pub(crate) mod interner;
 pub(crate) mod manually_move;
 pub(crate) mod scaling;
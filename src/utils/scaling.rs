@@ -11,9 +11,7 @@ impl UsizeScaler {
         match self {
             UsizeScaler::Linear { factor, limit } => {
                 let value = ((value as isize) + factor) as usize;
-                if *factor > 0 && value >= *limit {
-                    *limit
-                } else if *factor < 0 && value <= *limit {
+                if (*factor > 0 && value >= *limit) || (*factor < 0 && value <= *limit) {
                     *limit
                 } else {
                     value
@@ -59,9 +57,7 @@ impl RealScaler {
         match self {
             RealScaler::Linear { factor, limit } => {
                 let value = value + factor;
-                if *factor > 0.0 && value >= *limit {
-                    *limit
-                } else if *factor < 0.0 && value <= *limit {
+                if (*factor > 0.0 && value >= *limit) || (*factor < 0.0 && value <= *limit) {
                     *limit
                 } else {
                     value
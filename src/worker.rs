@@ -0,0 +1,270 @@
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+    ffi,
+    fs::File,
+    io::{self, Read, Write},
+    os::fd::FromRawFd,
+    panic,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Held from the creation of a pipe until the parent has closed its write end,
+// so that a worker forked by another slot never inherits it and keeps the
+// reader from seeing EOF.
+static FORK: Mutex<()> = Mutex::new(());
+
+pub(crate) enum Outcome<T> {
+    Exited(Vec<T>),
+    Failed(i32),
+    Signaled(i32),
+    Timeout,
+    Interrupted,
+}
+
+pub(crate) struct Limits {
+    pub(crate) record: Option<Duration>,
+    pub(crate) total: Option<Duration>,
+}
+
+// Runs `task` in a forked child process. The child inherits the whole address
+// space (including the workspace prepared by the helper), streams its records
+// back through a pipe and terminates without running any destructor.
+//
+// With several slots the tuner is multi-threaded when it forks. The child only
+// has the forking thread, and a lock held by another thread at that moment
+// (e.g. stdout while a result is logged) stays locked in the child, so `task`
+// and the runner should stick to async-signal-safe code where they can. A
+// worker stuck on such a lock is only caught by the timeouts.
+pub(crate) fn spawn<T, F>(
+    task: F,
+    limits: &Limits,
+    interrupted: &dyn Fn() -> bool,
+) -> io::Result<Outcome<T>>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce(&mut dyn FnMut(T)),
+{
+    let guard = FORK.lock().unwrap_or_else(|error| error.into_inner());
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let [reader, writer] = fds;
+
+    match unsafe { libc::fork() } {
+        -1 => {
+            let error = io::Error::last_os_error();
+            unsafe {
+                libc::close(reader);
+                libc::close(writer);
+            }
+            Err(error)
+        }
+        0 => {
+            unsafe {
+                libc::close(reader);
+            }
+            let mut writer = unsafe { File::from_raw_fd(writer) };
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                task(&mut |record| {
+                    let mut line = serde_json::to_vec(&record).unwrap();
+                    line.push(b'\n');
+                    let _ = writer.write_all(&line);
+                });
+            }));
+            unsafe { libc::_exit(if result.is_ok() { 0 } else { 1 }) }
+        }
+        pid => {
+            unsafe {
+                libc::close(writer);
+            }
+            drop(guard);
+            let mut file = unsafe { File::from_raw_fd(reader) };
+            let mut buffer = Vec::new();
+            let mut records = Vec::new();
+
+            let start = Instant::now();
+            let mut last = start;
+            loop {
+                let now = Instant::now();
+                let deadline = [
+                    limits.record.map(|limit| last + limit),
+                    limits.total.map(|limit| start + limit),
+                ]
+                .into_iter()
+                .flatten()
+                .min();
+                if deadline.is_some_and(|deadline| now >= deadline) {
+                    return terminate(pid, Outcome::Timeout);
+                }
+                if interrupted() {
+                    return terminate(pid, Outcome::Interrupted);
+                }
+
+                let timeout = deadline.map_or(POLL_INTERVAL, |deadline| {
+                    (deadline - now).min(POLL_INTERVAL)
+                });
+                let mut pollfd = libc::pollfd {
+                    fd: reader,
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let ready = unsafe {
+                    libc::poll(&mut pollfd, 1, (timeout.as_millis() as ffi::c_int).max(1))
+                };
+                if ready < 0 {
+                    let error = io::Error::last_os_error();
+                    if error.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    let _ = terminate::<T>(pid, Outcome::Interrupted);
+                    return Err(error);
+                }
+                if ready == 0 {
+                    continue;
+                }
+
+                let mut chunk = [0; 4096];
+                let n = match file.read(&mut chunk) {
+                    Ok(n) => n,
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(error) => {
+                        let _ = terminate::<T>(pid, Outcome::Interrupted);
+                        return Err(error);
+                    }
+                };
+                if n == 0 {
+                    break;
+                }
+
+                buffer.extend_from_slice(&chunk[..n]);
+                while let Some(position) = buffer.iter().position(|&b| b == b'\n') {
+                    let line = buffer.drain(..=position).collect::<Vec<_>>();
+                    if let Ok(record) = serde_json::from_slice(&line[..position]) {
+                        records.push(record);
+                        last = Instant::now();
+                    }
+                }
+            }
+
+            let status = wait(pid)?;
+            if libc::WIFSIGNALED(status) {
+                Ok(Outcome::Signaled(libc::WTERMSIG(status)))
+            } else if libc::WEXITSTATUS(status) != 0 {
+                Ok(Outcome::Failed(libc::WEXITSTATUS(status)))
+            } else {
+                Ok(Outcome::Exited(records))
+            }
+        }
+    }
+}
+
+fn wait(pid: libc::pid_t) -> io::Result<ffi::c_int> {
+    let mut status = 0;
+    loop {
+        if unsafe { libc::waitpid(pid, &mut status, 0) } != -1 {
+            return Ok(status);
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+fn terminate<T>(pid: libc::pid_t, outcome: Outcome<T>) -> io::Result<Outcome<T>> {
+    unsafe {
+        libc::kill(pid, libc::SIGKILL);
+    }
+    wait(pid)?;
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn run(task: impl FnOnce(&mut dyn FnMut(u32)), record: u64, total: u64) -> Outcome<u32> {
+        let limits = Limits {
+            record: (record > 0).then(|| Duration::from_millis(record)),
+            total: (total > 0).then(|| Duration::from_millis(total)),
+        };
+        spawn(task, &limits, &|| false).unwrap()
+    }
+
+    #[test]
+    fn test_exit() {
+        let outcome = run(
+            |record| {
+                record(1);
+                record(2);
+            },
+            0,
+            0,
+        );
+        assert!(matches!(outcome, Outcome::Exited(records) if records == [1, 2]));
+
+        let outcome = run(|_| unsafe { libc::_exit(3) }, 0, 0);
+        assert!(matches!(outcome, Outcome::Failed(3)));
+    }
+
+    #[test]
+    fn test_crash() {
+        // the default action, not the stack overflow handler of the runtime
+        let outcome = run(
+            |_| unsafe {
+                libc::signal(libc::SIGSEGV, libc::SIG_DFL);
+                libc::raise(libc::SIGSEGV);
+            },
+            0,
+            0,
+        );
+        assert!(matches!(outcome, Outcome::Signaled(libc::SIGSEGV)));
+    }
+
+    #[test]
+    fn test_timeout() {
+        // no record within the per-record limit
+        let start = Instant::now();
+        let outcome = run(
+            |record| {
+                record(1);
+                thread::sleep(Duration::from_secs(10));
+            },
+            200,
+            0,
+        );
+        assert!(matches!(outcome, Outcome::Timeout));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // records keep coming, but the whole run takes too long
+        let start = Instant::now();
+        let outcome = run(
+            |record| {
+                for i in 0..200 {
+                    record(i);
+                    thread::sleep(Duration::from_millis(50));
+                }
+            },
+            200,
+            500,
+        );
+        assert!(matches!(outcome, Outcome::Timeout));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let limits = Limits {
+            record: None,
+            total: None,
+        };
+        let task = |_: &mut dyn FnMut(u32)| thread::sleep(Duration::from_secs(10));
+        let outcome = spawn(task, &limits, &|| true).unwrap();
+        assert!(matches!(outcome, Outcome::Interrupted));
+    }
+}